@@ -1,4 +1,5 @@
-use std::{collections::HashMap, fs};
+use core::fmt;
+use std::{collections::HashMap, fs, path::Path};
 
 #[derive(PartialEq, Eq)]
 enum Direction {
@@ -12,7 +13,7 @@ enum Direction {
     Ground,
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Movement {
     Up,
     Down,
@@ -20,12 +21,7 @@ enum Movement {
     Right,
 }
 
-#[derive(PartialEq, Eq)]
-enum TileMarker {
-    Pipe,
-    Inside,
-    Outside,
-}
+type PipeMap = HashMap<(usize, usize), Direction>;
 
 //    L is a 90-degree bend connecting north and east.
 //    J is a 90-degree bend connecting north and west.
@@ -33,131 +29,134 @@ enum TileMarker {
 //    F is a 90-degree bend connecting south and east.
 //    . is ground; there is no pipe in this tile.
 
-fn recursive_map_pass(
-    pos: (usize, usize),
-    current_distance: u64,
-    map: &HashMap<(usize, usize), Direction>,
-    distance_map: &mut HashMap<(usize, usize), u64>,
-) {
-    if let Some(&cal_distance) = distance_map.get(&pos) {
-        if current_distance < cal_distance {
-            distance_map.insert(pos, current_distance);
-        } else {
-            return;
+impl Movement {
+    fn opposite(&self) -> Movement {
+        match self {
+            Movement::Up => Movement::Down,
+            Movement::Down => Movement::Up,
+            Movement::Left => Movement::Right,
+            Movement::Right => Movement::Left,
         }
-    };
-    distance_map.insert(pos, current_distance);
-    let new_distance = current_distance + 1;
-    let current_direction = match map.get(&pos) {
-        Some(dir) => dir,
-        None => &Direction::Start,
-    };
+    }
 
-    let north = match current_direction {
-        Direction::Start => true,
-        Direction::Vertical => true,
-        Direction::NorthWest => true,
-        Direction::NorthEast => true,
-        _ => false,
-    };
-    let south = match current_direction {
-        Direction::Start => true,
-        Direction::Vertical => true,
-        Direction::SouthWest => true,
-        Direction::SouthEast => true,
-        _ => false,
-    };
+    fn apply(&self, pos: (usize, usize)) -> Option<(usize, usize)> {
+        match self {
+            Movement::Up => Some((pos.0.checked_sub(1)?, pos.1)),
+            Movement::Down => Some((pos.0 + 1, pos.1)),
+            Movement::Left => Some((pos.0, pos.1.checked_sub(1)?)),
+            Movement::Right => Some((pos.0, pos.1 + 1)),
+        }
+    }
+}
 
-    let west = match current_direction {
-        Direction::Start => true,
-        Direction::Horizontal => true,
-        Direction::NorthWest => true,
-        Direction::SouthWest => true,
-        _ => false,
-    };
+impl Direction {
+    // Movements which leave the tile through one of its pipe ends
+    fn connections(&self) -> &'static [Movement] {
+        match self {
+            Direction::Vertical => &[Movement::Up, Movement::Down],
+            Direction::Horizontal => &[Movement::Left, Movement::Right],
+            Direction::NorthEast => &[Movement::Up, Movement::Right],
+            Direction::NorthWest => &[Movement::Up, Movement::Left],
+            Direction::SouthWest => &[Movement::Down, Movement::Left],
+            Direction::SouthEast => &[Movement::Down, Movement::Right],
+            Direction::Start => &[
+                Movement::Down,
+                Movement::Right,
+                Movement::Up,
+                Movement::Left,
+            ],
+            Direction::Ground => &[],
+        }
+    }
+}
 
-    let east = match current_direction {
-        Direction::Start => true,
-        Direction::Horizontal => true,
-        Direction::NorthEast => true,
-        Direction::SouthEast => true,
-        _ => false,
-    };
+#[derive(Debug, PartialEq, Eq)]
+enum LoopError {
+    MissingStart,
+    NoLoop,
+}
 
-    if pos.0 > 0 && north {
-        let upper_tile = (pos.0 - 1, pos.1);
-        if let Some(&ref dir) = map.get(&upper_tile) {
-            match dir {
-                Direction::Vertical => {
-                    recursive_map_pass(upper_tile, new_distance, map, distance_map)
-                }
-                Direction::SouthEast => {
-                    recursive_map_pass(upper_tile, new_distance, map, distance_map)
-                }
-                Direction::SouthWest => {
-                    recursive_map_pass(upper_tile, new_distance, map, distance_map)
-                }
-                _ => (),
-            };
+impl fmt::Display for LoopError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoopError::MissingStart => write!(f, "Map has no start tile"),
+            LoopError::NoLoop => write!(f, "Start tile is not part of any loop"),
         }
     }
-    let lower_tile = (pos.0 + 1, pos.1);
-    if south {
-        if let Some(&ref dir) = map.get(&lower_tile) {
-            match dir {
-                Direction::Vertical => {
-                    recursive_map_pass(lower_tile, new_distance, map, distance_map)
-                }
-                Direction::NorthEast => {
-                    recursive_map_pass(lower_tile, new_distance, map, distance_map)
-                }
-                Direction::NorthWest => {
-                    recursive_map_pass(lower_tile, new_distance, map, distance_map)
-                }
-                _ => (),
-            };
-        }
+}
+
+// Main loop in walking order, starting with S itself
+struct PipeLoop {
+    tiles: Vec<(usize, usize)>,
+}
+
+impl PipeLoop {
+    // Distance of every tile from S, going around the shorter way
+    fn distances(&self) -> Vec<u64> {
+        let len = self.tiles.len();
+        (0..len).map(|idx| idx.min(len - idx) as u64).collect()
     }
 
-    if pos.1 > 0 && west {
-        let left_tile = (pos.0, pos.1 - 1);
-        if let Some(&ref dir) = map.get(&left_tile) {
-            match dir {
-                Direction::Horizontal => {
-                    recursive_map_pass(left_tile, new_distance, map, distance_map)
-                }
-                Direction::NorthEast => {
-                    recursive_map_pass(left_tile, new_distance, map, distance_map)
-                }
-                Direction::SouthEast => {
-                    recursive_map_pass(left_tile, new_distance, map, distance_map)
-                }
-                _ => (),
-            };
+    fn farthest(&self) -> ((usize, usize), u64) {
+        let idx = self.tiles.len() / 2;
+        (self.tiles[idx], self.distances()[idx])
+    }
+
+    // Tiles enclosed by the loop, from Pick's theorem: area = inside + len / 2 - 1,
+    // with the area given by the shoelace formula. The absolute value makes it
+    // independent of the direction the loop was walked in.
+    fn enclosed_tiles(&self) -> usize {
+        let len = self.tiles.len();
+        let double_area: i64 = (0..len)
+            .map(|idx| {
+                let (row, col) = self.tiles[idx];
+                let (next_row, next_col) = self.tiles[(idx + 1) % len];
+                col as i64 * next_row as i64 - next_col as i64 * row as i64
+            })
+            .sum();
+        (double_area.unsigned_abs() as usize - len) / 2 + 1
+    }
+}
+
+// Follows the pipes from the start, which leads back to S only for the
+// main loop; other pipes end on ground or on a pipe not facing back
+fn follow_pipes(
+    map: &PipeMap,
+    start: (usize, usize),
+    first_movement: Movement,
+) -> Option<Vec<(usize, usize)>> {
+    let mut tiles = vec![start];
+    let mut movement = first_movement;
+    let mut pos = start;
+    while tiles.len() <= map.len() {
+        pos = movement.apply(pos)?;
+        let direction = map.get(&pos)?;
+        if !direction.connections().contains(&movement.opposite()) {
+            return None;
+        }
+        if pos == start {
+            return Some(tiles);
         }
+        tiles.push(pos);
+        movement = *direction
+            .connections()
+            .iter()
+            .find(|next| **next != movement.opposite())?;
     }
+    None
+}
 
-    let right_tile = (pos.0, pos.1 + 1);
-    if east {
-        if let Some(&ref dir) = map.get(&right_tile) {
-            match dir {
-                Direction::Horizontal => {
-                    recursive_map_pass(right_tile, new_distance, map, distance_map)
-                }
-                Direction::NorthWest => {
-                    recursive_map_pass(right_tile, new_distance, map, distance_map)
-                }
-                Direction::SouthWest => {
-                    recursive_map_pass(right_tile, new_distance, map, distance_map)
-                }
-                _ => (),
-            };
+fn trace_loop(map: &PipeMap, start: (usize, usize)) -> Result<PipeLoop, LoopError> {
+    for first_movement in Direction::Start.connections() {
+        if let Some(tiles) = follow_pipes(map, start, *first_movement) {
+            return Ok(PipeLoop { tiles });
         }
     }
+    Err(LoopError::NoLoop)
 }
 
-fn create_map(file_content: &str) -> HashMap<(usize, usize), Direction> {
-    let mut map: HashMap<(usize, usize), Direction> = HashMap::new();
+fn create_map(file_content: &str) -> PipeMap {
+    let mut map: PipeMap = HashMap::new();
 
     for (row_idx, row) in file_content.split("\n").enumerate() {
         for (col_idx, char) in row.chars().enumerate() {
@@ -176,398 +175,125 @@ fn create_map(file_content: &str) -> HashMap<(usize, usize), Direction> {
         }
     }
 
-    return map;
-}
-
-fn find_start(map: &HashMap<(usize, usize), Direction>) -> (usize, usize) {
-    let mut start_idx: (usize, usize) = (0, 0);
-    for (pos, val) in map.iter() {
-        match val {
-            Direction::Start => {
-                start_idx = *pos;
-            }
-            _ => (),
-        }
-    }
-    return start_idx;
-}
-
-fn print_map(max_coordinate: (usize, usize), tile_map: &HashMap<(usize, usize), TileMarker>) {
-    for i in 0..max_coordinate.0 {
-        for j in 0..max_coordinate.1 {
-            if let Some(&ref tile_marker) = tile_map.get(&(i, j)) {
-                let tile_char = match tile_marker {
-                    TileMarker::Pipe => '*',
-                    TileMarker::Outside => 'O',
-                    TileMarker::Inside => 'I',
-                };
-
-                print!("{}", tile_char);
-            } else {
-                print!("U");
-            }
-        }
-        print!("\n");
-    }
+    map
 }
 
-fn mark_tile_outside(
-    tile_idx: (usize, usize),
-    tile_map: &mut HashMap<(usize, usize), TileMarker>,
-) -> TileMarker {
-    match tile_map.get(&tile_idx) {
-        Some(tile_marker) => match tile_marker {
-            TileMarker::Outside => TileMarker::Outside,
-            TileMarker::Pipe => TileMarker::Pipe,
-            TileMarker::Inside => {
-                tile_map.insert(tile_idx, TileMarker::Outside);
-                TileMarker::Outside
-            }
-        },
-        None => {
-            tile_map.insert(tile_idx, TileMarker::Outside);
-            TileMarker::Outside
-        }
-    }
+fn find_start(map: &PipeMap) -> Result<(usize, usize), LoopError> {
+    map.iter()
+        .find(|(_, val)| **val == Direction::Start)
+        .map(|(pos, _)| *pos)
+        .ok_or(LoopError::MissingStart)
 }
 
-fn mark_tile_inside(
-    tile_idx: (usize, usize),
-    tile_map: &mut HashMap<(usize, usize), TileMarker>,
-) -> TileMarker {
-    match tile_map.get(&tile_idx) {
-        Some(tile_marker) => match tile_marker {
-            TileMarker::Inside => TileMarker::Inside,
-            TileMarker::Pipe => TileMarker::Pipe,
-            TileMarker::Outside => {
-                tile_map.insert(tile_idx, TileMarker::Inside);
-                TileMarker::Inside
-            }
-        },
-        None => {
-            tile_map.insert(tile_idx, TileMarker::Inside);
-            TileMarker::Inside
-        }
-    }
+fn load_loop(file_content: &str) -> Result<(PipeMap, PipeLoop), LoopError> {
+    let map = create_map(file_content);
+    let start_idx = find_start(&map)?;
+    let pipe_loop = trace_loop(&map, start_idx)?;
+    Ok((map, pipe_loop))
 }
 
-pub fn day10_task1() {
-    let input_filepath = match std::env::current_dir() {
-        //Ok(filepath) => filepath.join("input_d10_test"),
-        Ok(filepath) => filepath.join("input_d10_t01"),
-        Err(_) => panic!("Cannot find current directory"),
+pub fn solve_part_01(file_path: &Path) -> u64 {
+    let file_content = fs::read_to_string(file_path).expect("File could not be loaded");
+    let (_, pipe_loop) = match load_loop(&file_content) {
+        Ok(loaded) => loaded,
+        Err(err) => panic!("{}", err),
     };
 
-    println!("Input filepath: {}", input_filepath.display());
-
-    let file_content = fs::read_to_string(input_filepath).expect("File could not be loaded");
-
-    let mut distance_map: HashMap<(usize, usize), u64> = HashMap::new();
-    let map = create_map(&file_content);
-    let start_idx = find_start(&map);
-
-    recursive_map_pass(start_idx, 0, &map, &mut distance_map);
-
-    if let Some((max_idx, v)) = distance_map
-        .iter()
-        .max_by(|(_, val1), (_, val2)| val1.cmp(val2))
-    {
-        println!("{},{} {}", max_idx.0, max_idx.1, v);
-    }
+    pipe_loop.farthest().1
 }
 
-pub fn day10_task2() {
-    let input_filepath = match std::env::current_dir() {
-        //Ok(filepath) => filepath.join("input_d10_test"),
-        Ok(filepath) => filepath.join("input_d10_t01"),
-        Err(_) => panic!("Cannot find current directory"),
+pub fn solve_part_02(file_path: &Path) -> usize {
+    let file_content = fs::read_to_string(file_path).expect("File could not be loaded");
+    let (_, pipe_loop) = match load_loop(&file_content) {
+        Ok(loaded) => loaded,
+        Err(err) => panic!("{}", err),
     };
 
-    println!("Input filepath: {}", input_filepath.display());
-
-    let file_content = fs::read_to_string(input_filepath).expect("File could not be loaded");
+    pipe_loop.enclosed_tiles()
+}
 
-    let map = create_map(&file_content);
-    let start_idx = find_start(&map);
-    let mut distance_map: HashMap<(usize, usize), u64> = HashMap::new();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracing_loop() {
+        let (_, pipe_loop) = load_loop("-L|F7\n7S-7|\nL|7||\n-L-J|\nL|-JF\n").unwrap();
+        assert_eq!(
+            pipe_loop.tiles,
+            vec![
+                (1, 1),
+                (2, 1),
+                (3, 1),
+                (3, 2),
+                (3, 3),
+                (2, 3),
+                (1, 3),
+                (1, 2)
+            ]
+        );
+        assert_eq!(pipe_loop.enclosed_tiles(), 1);
+        assert_eq!(pipe_loop.distances(), vec![0, 1, 2, 3, 4, 3, 2, 1]);
+        assert_eq!(pipe_loop.farthest(), ((3, 3), 4));
+    }
 
-    recursive_map_pass(start_idx, 0, &map, &mut distance_map);
+    #[test]
+    fn test_enclosed_tiles_in_both_directions() {
+        // Walked counterclockwise from the bottom edge, clockwise from the top
+        let (_, counterclockwise) = load_loop("F--7\n|..|\n|..|\nL-SJ\n").unwrap();
+        assert_eq!(counterclockwise.tiles[1], (3, 3));
+        assert_eq!(counterclockwise.enclosed_tiles(), 4);
+        let (_, clockwise) = load_loop(".F-S7.\n.|..|.\n.|..|.\n.L--J.\n").unwrap();
+        assert_eq!(clockwise.tiles[1], (0, 4));
+        assert_eq!(clockwise.enclosed_tiles(), 4);
+    }
 
-    if let Some((max_idx, v)) = distance_map
-        .iter()
-        .max_by(|(_, val1), (_, val2)| val1.cmp(val2))
-    {
-        println!("{},{} {}", max_idx.0, max_idx.1, v);
+    #[test]
+    fn test_missing_loop() {
+        assert_eq!(
+            load_loop("...\n.-.\n...\n").err(),
+            Some(LoopError::MissingStart)
+        );
+        assert_eq!(load_loop("F-.\nLS.\n...\n").err(), Some(LoopError::NoLoop));
+        assert_eq!(load_loop("S-7\n|.|\nL--\n").err(), Some(LoopError::NoLoop));
     }
 
-    let mut tile_map: HashMap<(usize, usize), TileMarker> = HashMap::new();
+    #[test]
+    fn test_long_loop() {
+        // Border of a square, far too long for one recursion per tile
+        let size = 1000;
+        let mut rows = vec![format!("S{}7", "-".repeat(size - 2))];
+        rows.extend((0..size - 2).map(|_| format!("|{}|", ".".repeat(size - 2))));
+        rows.push(format!("L{}J", "-".repeat(size - 2)));
+        let (_, pipe_loop) = load_loop(&rows.join("\n")).unwrap();
+        assert_eq!(pipe_loop.tiles.len(), 4 * (size - 1));
+        assert_eq!(
+            pipe_loop.farthest(),
+            ((size - 1, size - 1), 2 * (size as u64 - 1))
+        );
+    }
 
-    let mut movement_direction = Movement::Down;
-    let mut loop_item = start_idx;
-    for (item_idx, distance) in distance_map.iter() {
-        if *distance == (1 as u64) {
-            loop_item = item_idx.clone();
-            break;
-        }
+    #[test]
+    fn test_solving_d10_part1() {
+        let input_filepath = match std::env::current_dir() {
+            Ok(filepath) => filepath.join("input_d10_test"),
+            Err(_) => panic!("Cannot find current directory"),
+        };
+        assert_eq!(solve_part_01(&input_filepath), 4);
+
+        let input_filepath = match std::env::current_dir() {
+            Ok(filepath) => filepath.join("input_d10_test2"),
+            Err(_) => panic!("Cannot find current directory"),
+        };
+        assert_eq!(solve_part_01(&input_filepath), 8);
     }
-    println!("Starting point {} {}", loop_item.0, loop_item.1);
-
-    if let Some(&max_coordinate) = map.keys().max() {
-        loop {
-            if loop_item == start_idx {
-                break;
-            }
-            let (loop_item_row_idx, loop_item_col_idx) = loop_item;
-            if let Some(&ref direction) = map.get(&(loop_item_row_idx, loop_item_col_idx)) {
-                tile_map.insert((loop_item_row_idx, loop_item_col_idx), TileMarker::Pipe);
-                movement_direction = match movement_direction {
-                    Movement::Up => {
-                        for row in (0..loop_item_row_idx).rev() {
-                            let tile_coords = (row, loop_item_col_idx);
-                            match mark_tile_outside(tile_coords, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-                        for col in loop_item_col_idx + 1..=max_coordinate.1 {
-                            let tile_coords = (loop_item_row_idx, col);
-                            match mark_tile_outside(tile_coords, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-                        for col in (0..loop_item_col_idx).rev() {
-                            let tile_coords = (loop_item_row_idx, col);
-                            match mark_tile_inside(tile_coords, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-                        loop_item = match direction {
-                            Direction::SouthWest => (loop_item_row_idx, loop_item_col_idx - 1),
-                            Direction::SouthEast => (loop_item_row_idx, loop_item_col_idx + 1),
-                            _ => (loop_item_row_idx - 1, loop_item_col_idx),
-                        };
-                        match direction {
-                            Direction::SouthWest => Movement::Left,
-                            Direction::SouthEast => Movement::Right,
-                            _ => movement_direction,
-                        }
-                    }
-                    Movement::Down => {
-                        for row in loop_item_row_idx + 1..=max_coordinate.0 {
-                            let tile_coords = (row, loop_item_col_idx);
-                            match mark_tile_outside(tile_coords, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-                        for col in loop_item_col_idx + 1..=max_coordinate.1 {
-                            let tile_coords = (loop_item_row_idx, col);
-                            match mark_tile_inside(tile_coords, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-                        for col in (0..loop_item_col_idx).rev() {
-                            let tile_coords = (loop_item_row_idx, col);
-                            match mark_tile_outside(tile_coords, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-
-                        loop_item = match direction {
-                            Direction::NorthEast => (loop_item_row_idx, loop_item_col_idx + 1),
-                            Direction::NorthWest => (loop_item_row_idx, loop_item_col_idx - 1),
-                            _ => (loop_item_row_idx + 1, loop_item_col_idx),
-                        };
-
-                        match direction {
-                            Direction::NorthWest => Movement::Left,
-                            Direction::NorthEast => Movement::Right,
-                            _ => movement_direction,
-                        }
-                    }
-                    Movement::Left => {
-                        for col in (0..loop_item_col_idx).rev() {
-                            let item_idx = (loop_item_row_idx, col);
-                            match mark_tile_outside(item_idx, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-                        for row in loop_item_row_idx + 1..=max_coordinate.0 {
-                            let item_idx = (row, loop_item_col_idx);
-                            match mark_tile_inside(item_idx, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-                        for row in (0..loop_item_row_idx).rev() {
-                            let item_idx = (row, loop_item_col_idx);
-                            match mark_tile_outside(item_idx, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-
-                        loop_item = match direction {
-                            Direction::NorthEast => (loop_item_row_idx - 1, loop_item_col_idx),
-                            Direction::SouthEast => (loop_item_row_idx + 1, loop_item_col_idx),
-                            _ => (loop_item_row_idx, loop_item_col_idx - 1),
-                        };
-                        match direction {
-                            Direction::SouthEast => Movement::Down,
-                            Direction::NorthEast => Movement::Up,
-                            _ => movement_direction,
-                        }
-                    }
-                    Movement::Right => {
-                        for col in loop_item_col_idx + 1..=max_coordinate.1 {
-                            let item_idx = (loop_item_row_idx, col);
-                            match mark_tile_outside(item_idx, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-                        for row in loop_item_row_idx + 1..=max_coordinate.0 {
-                            let item_idx = (row, loop_item_col_idx);
-                            match mark_tile_outside(item_idx, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-                        for row in (0..loop_item_row_idx).rev() {
-                            let item_idx = (row, loop_item_col_idx);
-                            match mark_tile_inside(item_idx, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-                        loop_item = match direction {
-                            Direction::NorthWest => (loop_item_row_idx - 1, loop_item_col_idx),
-                            Direction::SouthWest => (loop_item_row_idx + 1, loop_item_col_idx),
-                            _ => (loop_item_row_idx, loop_item_col_idx + 1),
-                        };
-                        match direction {
-                            Direction::SouthWest => Movement::Down,
-                            Direction::NorthWest => Movement::Up,
-                            _ => movement_direction,
-                        }
-                    }
-                };
-                match movement_direction {
-                    Movement::Up => {
-                        for row in (0..loop_item_row_idx).rev() {
-                            let tile_coords = (row, loop_item_col_idx);
-                            match mark_tile_outside(tile_coords, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-                        for col in loop_item_col_idx + 1..=max_coordinate.1 {
-                            let tile_coords = (loop_item_row_idx, col);
-                            match mark_tile_outside(tile_coords, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-                        for col in (0..loop_item_col_idx).rev() {
-                            let tile_coords = (loop_item_row_idx, col);
-                            match mark_tile_inside(tile_coords, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-                    }
-                    Movement::Down => {
-                        for row in loop_item_row_idx + 1..=max_coordinate.0 {
-                            let tile_coords = (row, loop_item_col_idx);
-                            match mark_tile_outside(tile_coords, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-                        for col in loop_item_col_idx + 1..=max_coordinate.1 {
-                            let tile_coords = (loop_item_row_idx, col);
-                            match mark_tile_inside(tile_coords, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-                        for col in (0..loop_item_col_idx).rev() {
-                            let tile_coords = (loop_item_row_idx, col);
-                            match mark_tile_outside(tile_coords, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-                    }
-                    Movement::Left => {
-                        for col in (0..loop_item_col_idx).rev() {
-                            let item_idx = (loop_item_row_idx, col);
-                            match mark_tile_outside(item_idx, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-                        for row in loop_item_row_idx + 1..=max_coordinate.0 {
-                            let item_idx = (row, loop_item_col_idx);
-                            match mark_tile_inside(item_idx, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-                        for row in (0..loop_item_row_idx).rev() {
-                            let item_idx = (row, loop_item_col_idx);
-                            match mark_tile_outside(item_idx, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-                    }
-                    Movement::Right => {
-                        for col in loop_item_col_idx + 1..=max_coordinate.1 {
-                            let item_idx = (loop_item_row_idx, col);
-                            match mark_tile_outside(item_idx, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-                        for row in loop_item_row_idx + 1..=max_coordinate.0 {
-                            let item_idx = (row, loop_item_col_idx);
-                            match mark_tile_outside(item_idx, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-                        for row in (0..loop_item_row_idx).rev() {
-                            let item_idx = (row, loop_item_col_idx);
-                            match mark_tile_inside(item_idx, &mut tile_map) {
-                                TileMarker::Pipe => break,
-                                _ => (),
-                            }
-                        }
-                    }
-                };
-            }
-            //print_map(max_coordinate, &tile_map);
-
-            //match movement_direction {
-            //   Movement::Up => println!("Going UP"),
-            //   Movement::Down => println!("Going DOWN"),
-            //   Movement::Left => println!("Going LEFT"),
-            //   Movement::Right => println!("Going RIGHT"),
-            //};
-        }
-        print_map(max_coordinate, &tile_map);
-        println!(
-            "Inside {}",
-            tile_map
-                .values()
-                .filter(|&x| *x == TileMarker::Inside)
-                .count()
-        );
+
+    #[test]
+    fn test_solving_d10_part2() {
+        let input_filepath = match std::env::current_dir() {
+            Ok(filepath) => filepath.join("input_d10_test3"),
+            Err(_) => panic!("Cannot find current directory"),
+        };
+        assert_eq!(solve_part_02(&input_filepath), 4);
     }
 }
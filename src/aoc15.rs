@@ -7,7 +7,7 @@ fn hashing_algorithm(s: &str) -> u8 {
 
     for s in s.chars() {
         let cur: u32 = ((hash + (s as u32)) * 17) % 256;
-        hash = cur.try_into().unwrap();
+        hash = cur;
     }
 
     hash.try_into().unwrap()
@@ -53,23 +53,17 @@ pub fn solve_part_02(file_path: &Path) -> u64 {
             if let Some(stripped_elem) = elem.strip_suffix("-") {
                 let hash = hashing_algorithm(stripped_elem);
                 let mut idx: Option<usize> = None;
-                match &mut hash_map.get_mut(&hash) {
-                    Some(vec) => {
-                        for (i, (name, _)) in vec.iter().enumerate() {
-                            if name == stripped_elem {
-                                idx = Some(i);
-                                break;
-                            }
+                if let Some(vec) = &mut hash_map.get_mut(&hash) {
+                    for (i, (name, _)) in vec.iter().enumerate() {
+                        if name == stripped_elem {
+                            idx = Some(i);
+                            break;
                         }
-                        match idx {
-                            Some(el_index) => {
-                                let (name, lens) = vec.remove(el_index);
-                                println!("Removing: {} {}", name, lens);
-                            }
-                            None => (),
-                        };
                     }
-                    None => (),
+                    if let Some(el_index) = idx {
+                        let (name, lens) = vec.remove(el_index);
+                        println!("Removing: {} {}", name, lens);
+                    }
                 }
             }
             continue;
@@ -79,29 +73,26 @@ pub fn solve_part_02(file_path: &Path) -> u64 {
             let (name, lens_len) = (splitted_vec[0], splitted_vec[1].parse::<u8>().unwrap());
             println!("{} {}", name, lens_len);
             let hash = hashing_algorithm(name);
-            match &mut hash_map.get_mut(&hash) {
-                Some(vec) => {
-                    let mut idx: Option<usize> = None;
-                    for (i, (item_name, _)) in vec.iter().enumerate() {
-                        if name == item_name {
-                            idx = Some(i);
-                            break;
-                        }
+            if let Some(vec) = &mut hash_map.get_mut(&hash) {
+                let mut idx: Option<usize> = None;
+                for (i, (item_name, _)) in vec.iter().enumerate() {
+                    if name == item_name {
+                        idx = Some(i);
+                        break;
                     }
-                    match idx {
-                        Some(el_index) => {
-                            let (name, lens) = vec.remove(el_index);
-                            println!("Removing: {} {}", name, lens);
-                            println!("Inserting: {} {}", name, lens_len);
-                            vec.insert(el_index, (name.to_string(), lens_len));
-                        }
-                        None => {
-                            println!("Inserting: {} {}", name, lens_len);
-                            vec.push((name.to_string(), lens_len));
-                        }
-                    };
                 }
-                None => (),
+                match idx {
+                    Some(el_index) => {
+                        let (name, lens) = vec.remove(el_index);
+                        println!("Removing: {} {}", name, lens);
+                        println!("Inserting: {} {}", name, lens_len);
+                        vec.insert(el_index, (name.to_string(), lens_len));
+                    }
+                    None => {
+                        println!("Inserting: {} {}", name, lens_len);
+                        vec.push((name.to_string(), lens_len));
+                    }
+                };
             }
         }
     }
@@ -1,13 +1,14 @@
-use std::{collections::HashMap, fs, u64};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::Path,
+};
 
 fn parse_game_id(game_name: &str) -> u64 {
     let splitted_name: Vec<_> = game_name.split(" ").collect();
     if splitted_name.len() == 2 {
         let game_id = splitted_name[1];
-        match game_id.parse::<u64>() {
-            Ok(id) => id,
-            Err(_) => 0,
-        }
+        game_id.parse::<u64>().unwrap_or_default()
     } else {
         0
     }
@@ -27,10 +28,167 @@ fn parse_draw_item(draw: &str) -> Option<(&str, u64)> {
     None
 }
 
+struct Game<'a> {
+    id: u64,
+    draws: Vec<HashMap<&'a str, u64>>,
+}
+
+fn parse_game(game_line: &str) -> Option<Game<'_>> {
+    let splitted_game: Vec<_> = game_line.split(":").collect();
+    if splitted_game.len() != 2 {
+        return None;
+    }
+
+    let (game_id, game) = (splitted_game[0], splitted_game[1]);
+    let mut draws = Vec::new();
+    for draw in game.split(";") {
+        let mut cubes_map: HashMap<&str, u64> = HashMap::new();
+        for draw_item in draw.split(",") {
+            if let Some((item, count)) = parse_draw_item(draw_item) {
+                *cubes_map.entry(item).or_insert(0) += count;
+            }
+        }
+        draws.push(cubes_map);
+    }
+
+    Some(Game {
+        id: parse_game_id(game_id),
+        draws,
+    })
+}
+
+fn load_games(file_content: &str) -> Vec<Game<'_>> {
+    file_content.split("\n").filter_map(parse_game).collect()
+}
+
+// Minimal number of cubes of each color the bag must have held for the game
+fn minimal_bag<'a>(game: &Game<'a>) -> BTreeMap<&'a str, u64> {
+    let mut bag: BTreeMap<&str, u64> = BTreeMap::new();
+    for draw in &game.draws {
+        for (&item, &count) in draw {
+            let current = bag.entry(item).or_insert(0);
+            if count > *current {
+                *current = count;
+            }
+        }
+    }
+    bag
+}
+
+// Single bag that makes every game possible, i.e. color-wise maximum of minimal bags
+fn smallest_common_bag<'a>(games: &[Game<'a>]) -> BTreeMap<&'a str, u64> {
+    let mut common_bag: BTreeMap<&str, u64> = BTreeMap::new();
+    for game in games {
+        for (item, count) in minimal_bag(game) {
+            let current = common_bag.entry(item).or_insert(0);
+            if count > *current {
+                *current = count;
+            }
+        }
+    }
+    common_bag
+}
+
+fn is_game_possible(game: &Game, cubes_rules: &HashMap<&str, u64>) -> bool {
+    minimal_bag(game)
+        .iter()
+        .all(|(item, count)| count <= cubes_rules.get(item).unwrap_or(&0))
+}
+
+// Ids of games which are not possible under given rules, but become
+// possible once the limit for `color` is raised by `raise_by`
+fn games_possible_after_raise(
+    games: &[Game],
+    cubes_rules: &HashMap<&str, u64>,
+    color: &str,
+    raise_by: u64,
+) -> Vec<u64> {
+    let mut raised_rules = cubes_rules.clone();
+    *raised_rules.entry(color).or_insert(0) += raise_by;
+
+    games
+        .iter()
+        .filter(|game| {
+            !is_game_possible(game, cubes_rules) && is_game_possible(game, &raised_rules)
+        })
+        .map(|game| game.id)
+        .collect()
+}
+
+// For every color: how many games needed at least given number of cubes
+// (color -> minimal count -> number of games)
+fn max_distribution<'a>(games: &[Game<'a>]) -> BTreeMap<&'a str, BTreeMap<u64, usize>> {
+    let mut distribution: BTreeMap<&str, BTreeMap<u64, usize>> = BTreeMap::new();
+    for game in games {
+        for (item, count) in minimal_bag(game) {
+            *distribution
+                .entry(item)
+                .or_default()
+                .entry(count)
+                .or_insert(0) += 1;
+        }
+    }
+    distribution
+}
+
+pub fn day02_query(file_path: &Path, raise_by: u64) {
+    let cubes_rules = HashMap::from([("red", 12), ("green", 13), ("blue", 14)]);
+    let file_content = fs::read_to_string(file_path).expect("File could not be loaded");
+    let games = load_games(&file_content);
+    let colors: Vec<_> = smallest_common_bag(&games).into_keys().collect();
+
+    println!("Minimal bag per game");
+    print!("{:>6}", "game");
+    for color in &colors {
+        print!(" {:>6}", color);
+    }
+    println!(" {:>8}", "power");
+    for game in &games {
+        let bag = minimal_bag(game);
+        print!("{:>6}", game.id);
+        for color in &colors {
+            print!(" {:>6}", bag.get(color).unwrap_or(&0));
+        }
+        println!(" {:>8}", bag.values().product::<u64>());
+    }
+
+    println!("\nSmallest bag making every game possible");
+    for (color, count) in smallest_common_bag(&games) {
+        println!("{:>6} {:>6}", color, count);
+    }
+
+    println!(
+        "\nGames becoming possible when a limit is raised by {}",
+        raise_by
+    );
+    println!("{:>6} {:>6} {:>6}  games", "color", "from", "to");
+    for color in &colors {
+        let limit = cubes_rules.get(color).unwrap_or(&0);
+        let game_ids: Vec<_> = games_possible_after_raise(&games, &cubes_rules, color, raise_by)
+            .iter()
+            .map(|id| id.to_string())
+            .collect();
+        println!(
+            "{:>6} {:>6} {:>6}  {}",
+            color,
+            limit,
+            limit + raise_by,
+            game_ids.join(", ")
+        );
+    }
+
+    println!("\nDistribution of per-game maximums");
+    for (color, counts) in max_distribution(&games) {
+        println!("{}", color);
+        println!("{:>6} {:>6}", "max", "games");
+        for (count, games_count) in counts {
+            println!("{:>6} {:>6}", count, games_count);
+        }
+    }
+}
+
 pub fn day02_task01() {
-    println!("{} {}", "Game 132", parse_game_id("Game 132"));
     let cubes_rules = HashMap::from([("red", 12), ("green", 13), ("blue", 14)]);
-    let mut game_ids_sum = 0;
 
     let input_filepath = match std::env::current_dir() {
         Ok(filepath) => filepath.join("input_d02_t01"),
@@ -40,84 +198,86 @@ pub fn day02_task01() {
     println!("Input filepath: {}", input_filepath.display());
 
     let file_content = fs::read_to_string(input_filepath).expect("File could not be loaded");
-
-    for game_line in file_content.split("\n") {
-        let splitted_game: Vec<_> = game_line.split(":").collect();
-        if splitted_game.len() == 2 {
-            let mut game_accepted = true;
-
-            let (game_id, game) = (splitted_game[0], splitted_game[1]);
-            let game_id = parse_game_id(game_id);
-            for draw in game.split(";") {
-                let mut cubes_map: HashMap<&str, u64> = HashMap::new();
-                for draw_item in draw.split(",") {
-                    if let Some((item, count)) = parse_draw_item(draw_item) {
-                        let curr_val = match cubes_map.get(item) {
-                            Some(val) => val,
-                            None => &0,
-                        };
-                        cubes_map.insert(item, curr_val + count);
-                    }
-                }
-                for (item, count) in &cubes_map {
-                    if count > &cubes_rules[item] {
-                        game_accepted = false;
-                        println!("Game {} not accepted, because {}={}", game_id, item, count);
-                        break;
-                    }
-                }
-                if !game_accepted {
-                    break;
-                }
-            }
-            if !game_accepted {
-                continue;
-            }
-
-            println!("Game: {} '{}'", game_line, game_accepted);
-            if game_accepted {
-                println!("Accepted: {}", game_id);
-                game_ids_sum += game_id;
-            }
-        }
-    }
+    let game_ids_sum: u64 = load_games(&file_content)
+        .iter()
+        .filter(|game| is_game_possible(game, &cubes_rules))
+        .map(|game| game.id)
+        .sum();
     println!("GameIds sum: {}", game_ids_sum);
 }
 
 pub fn day02_task02() {
-    let mut minimum_cubes_power_sum = 0;
-
     let input_filepath = match std::env::current_dir() {
         Ok(filepath) => filepath.join("input_d02_t01"),
         Err(_) => panic!("Cannot find current directory"),
     };
 
     let file_content = fs::read_to_string(input_filepath).expect("File could not be loaded");
+    let minimum_cubes_power_sum: u64 = load_games(&file_content)
+        .iter()
+        .map(|game| minimal_bag(game).values().product::<u64>())
+        .sum();
+    println!("Sum of powers {}", minimum_cubes_power_sum);
+}
 
-    for game_line in file_content.split("\n") {
-        let splitted_game: Vec<_> = game_line.split(":").collect();
-        let mut cubes_map: HashMap<&str, u64> = HashMap::new();
-        if splitted_game.len() == 2 {
-            let game = splitted_game[1];
-            for draw in game.split(";") {
-                for draw_item in draw.split(",") {
-                    if let Some((item, count)) = parse_draw_item(draw_item) {
-                        let curr_val = match cubes_map.get(item) {
-                            Some(val) => val,
-                            None => &0,
-                        };
-                        if count > *curr_val {
-                            cubes_map.insert(item, count);
-                        }
-                    }
-                }
-                println!("Minimal number of cubes");
-                for (k, v) in &cubes_map {
-                    println!("{} {}", k, v);
-                }
-            }
-            minimum_cubes_power_sum += cubes_map.values().fold(1, |mul, v| mul*v);
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load_test_input() -> String {
+        let input_filepath = match std::env::current_dir() {
+            Ok(filepath) => filepath.join("input_d02_test"),
+            Err(_) => panic!("Cannot find current directory"),
+        };
+        fs::read_to_string(input_filepath).expect("File could not be loaded")
+    }
+
+    #[test]
+    fn test_minimal_bag() {
+        let file_content = load_test_input();
+        let games = load_games(&file_content);
+        assert_eq!(games.len(), 5);
+        assert_eq!(
+            minimal_bag(&games[0]),
+            BTreeMap::from([("blue", 6), ("green", 2), ("red", 4)])
+        );
+        let powers: u64 = games
+            .iter()
+            .map(|game| minimal_bag(game).values().product::<u64>())
+            .sum();
+        assert_eq!(powers, 2286);
+    }
+
+    #[test]
+    fn test_smallest_common_bag() {
+        let file_content = load_test_input();
+        let games = load_games(&file_content);
+        assert_eq!(
+            smallest_common_bag(&games),
+            BTreeMap::from([("blue", 15), ("green", 13), ("red", 20)])
+        );
+    }
+
+    #[test]
+    fn test_games_possible_after_raise() {
+        let file_content = load_test_input();
+        let games = load_games(&file_content);
+        let cubes_rules = HashMap::from([("red", 12), ("green", 13), ("blue", 14)]);
+        assert_eq!(
+            games_possible_after_raise(&games, &cubes_rules, "red", 8),
+            vec![3]
+        );
+        assert!(games_possible_after_raise(&games, &cubes_rules, "blue", 1).is_empty());
+    }
+
+    #[test]
+    fn test_max_distribution() {
+        let file_content = load_test_input();
+        let games = load_games(&file_content);
+        let distribution = max_distribution(&games);
+        assert_eq!(
+            distribution["green"],
+            BTreeMap::from([(2, 1), (3, 3), (13, 1)])
+        );
     }
-    println!("Sum of powers {}", minimum_cubes_power_sum);
 }
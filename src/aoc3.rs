@@ -1,11 +1,11 @@
-use std::{
-    collections::{HashMap, HashSet},
-    fs,
-};
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::tokenizer::{self, TokenStream};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Number {
-    value: u64,
+    value: i64,
+    line_idx: usize,
     start_index: usize,
     number_len: usize,
 }
@@ -18,310 +18,392 @@ struct DefinedSymbol {
 }
 
 enum Token {
-    Symbol(usize),
     DefinedSymbol(DefinedSymbol),
     Number(Number),
 }
 
-fn tokenizer(line: &str, line_index: usize) -> Vec<Token> {
-    let mut tokens = Vec::new();
-    let mut number = String::new();
-
-    let clear_number = |idx: &usize, number: &mut String, tokens: &mut Vec<Token>| {
-        if !number.is_empty() {
-            match number.parse::<u64>() {
-                Ok(val) => {
-                    tokens.push(Token::Number(Number {
-                        value: val,
-                        start_index: idx - number.len(),
-                        number_len: number.len(),
-                    }));
-                    number.clear();
+fn tokenizer(line: &str, line_index: usize, signed_numbers: bool) -> Vec<Token> {
+    TokenStream::new(line, line_index)
+        .signed_numbers(signed_numbers)
+        .map(|token| match token {
+            tokenizer::Token::Number {
+                value,
+                position,
+                len,
+            } => Token::Number(Number {
+                value,
+                line_idx: position.line_idx,
+                start_index: position.col_idx,
+                number_len: len,
+            }),
+            tokenizer::Token::Symbol { value, position } => Token::DefinedSymbol(DefinedSymbol {
+                value,
+                line_idx: position.line_idx,
+                col_idx: position.col_idx,
+            }),
+            tokenizer::Token::InvalidNumber { position, len } => panic!(
+                "Number '{}' on line {} does not fit into i64",
+                &line[position.byte_idx..position.byte_idx + len],
+                position.line_idx + 1
+            ),
+        })
+        .collect()
+}
+
+struct Schematic {
+    numbers: Vec<Number>,
+    symbols: HashMap<(usize, usize), DefinedSymbol>,
+}
+
+impl Schematic {
+    fn from_str(file_content: &str, signed_numbers: bool) -> Schematic {
+        let mut numbers = Vec::new();
+        let mut symbols = HashMap::new();
+
+        for (line_idx, engine_schematic_line) in file_content.split("\n").enumerate() {
+            for token in tokenizer(engine_schematic_line, line_idx, signed_numbers) {
+                match token {
+                    Token::Number(number) => numbers.push(number),
+                    Token::DefinedSymbol(symbol) => {
+                        symbols.insert((symbol.line_idx, symbol.col_idx), symbol);
+                    }
                 }
-                Err(_) => (),
-            };
-            number.clear();
+            }
         }
-    };
 
-    for (idx, char) in line.chars().enumerate() {
-        match char {
-            char if char.is_digit(10) => number += &char.to_string(),
-            '.' => {
-                clear_number(&idx, &mut number, &mut tokens);
+        Schematic { numbers, symbols }
+    }
+
+    // Symbols in any of the 8 directions around the number
+    fn adjacent_symbols(&self, number: &Number) -> Vec<DefinedSymbol> {
+        let mut adjacent = Vec::new();
+        let min_line = number.line_idx.saturating_sub(1);
+        let min_col = number.start_index.saturating_sub(1);
+        for line_idx in min_line..=number.line_idx + 1 {
+            for col_idx in min_col..=number.start_index + number.number_len {
+                if let Some(symbol) = self.symbols.get(&(line_idx, col_idx)) {
+                    adjacent.push(*symbol);
+                }
             }
-            symbol => {
-                clear_number(&idx, &mut number, &mut tokens);
-                tokens.push(Token::Symbol(idx));
-                tokens.push(Token::DefinedSymbol(DefinedSymbol {
-                    value: symbol,
-                    line_idx: line_index,
-                    col_idx: idx,
-                }));
+        }
+        adjacent
+    }
+
+    fn part_numbers(&self) -> Vec<Number> {
+        self.numbers
+            .iter()
+            .filter(|number| !self.adjacent_symbols(number).is_empty())
+            .copied()
+            .collect()
+    }
+
+    fn symbols_with_adjacent_numbers(&self) -> HashMap<DefinedSymbol, Vec<Number>> {
+        let mut symbols_map: HashMap<DefinedSymbol, Vec<Number>> = HashMap::new();
+        for number in &self.numbers {
+            for symbol in self.adjacent_symbols(number) {
+                symbols_map.entry(symbol).or_default().push(*number);
             }
         }
+        symbols_map
+    }
+
+    // Symbols with given value adjacent to exactly `arity` numbers,
+    // ordered by their position in the schematic
+    fn gears(&self, symbol_value: char, arity: usize) -> Vec<(DefinedSymbol, Vec<Number>)> {
+        let mut adjacent_numbers = self.symbols_with_adjacent_numbers();
+        let mut gears: Vec<_> = self
+            .symbols
+            .values()
+            .filter(|symbol| symbol.value == symbol_value)
+            .map(|symbol| (*symbol, adjacent_numbers.remove(symbol).unwrap_or_default()))
+            .filter(|(_, numbers)| numbers.len() == arity)
+            .collect();
+        gears.sort_by_key(|(symbol, _)| (symbol.line_idx, symbol.col_idx));
+        gears
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Product,
+    Sum,
+    Max,
+}
+
+impl Aggregate {
+    fn apply(&self, numbers: &[Number]) -> i64 {
+        let values = numbers.iter().map(|number| number.value);
+        match self {
+            Aggregate::Product => values.product(),
+            Aggregate::Sum => values.sum(),
+            Aggregate::Max => values.max().unwrap_or(0),
+        }
     }
-    clear_number(&line.len(), &mut number, &mut tokens);
+}
 
-    tokens
+pub fn parse_aggregate(name: &str) -> Option<Aggregate> {
+    match name {
+        "product" => Some(Aggregate::Product),
+        "sum" => Some(Aggregate::Sum),
+        "max" => Some(Aggregate::Max),
+        _ => None,
+    }
 }
 
-pub fn day03_task01() {
-    let input_filepath = match std::env::current_dir() {
-        Ok(filepath) => filepath.join("input_d03_t01"),
-        //Ok(filepath) => filepath.join("input_d03_test"),
-        Err(_) => panic!("Cannot find current directory"),
-    };
+fn gear_values(
+    schematic: &Schematic,
+    symbol_value: char,
+    arity: usize,
+    aggregate: Aggregate,
+) -> Vec<(DefinedSymbol, Vec<Number>, i64)> {
+    schematic
+        .gears(symbol_value, arity)
+        .into_iter()
+        .map(|(symbol, numbers)| {
+            let value = aggregate.apply(&numbers);
+            (symbol, numbers, value)
+        })
+        .collect()
+}
 
-    println!("Input filepath: {}", input_filepath.display());
+fn load_schematic(file_path: &Path, signed_numbers: bool) -> Schematic {
+    let file_content = fs::read_to_string(file_path).expect("File could not be loaded");
+    Schematic::from_str(&file_content, signed_numbers)
+}
 
-    let file_content = fs::read_to_string(input_filepath).expect("File could not be loaded");
+pub fn sum_part_numbers(file_path: &Path, signed_numbers: bool) -> i64 {
+    let schematic = load_schematic(file_path, signed_numbers);
 
-    let mut prev_symbols = Vec::new();
-    let mut prev_numbers = Vec::new();
-    let mut digits_sum: u64 = 0;
-    for (line_idx, engine_schematic_line) in file_content.split("\n").enumerate() {
-        let current_line_tokens = tokenizer(engine_schematic_line, line_idx);
-        let symbols: Vec<_> = current_line_tokens
-            .iter()
-            .filter_map(|i| match *i {
-                Token::Symbol(symbol_idx) => Some(symbol_idx),
-                _ => None,
-            })
-            .collect();
-        let mut numbers: Vec<_> = Vec::new();
-        let mut used_numbers: HashSet<_> = HashSet::new();
-        for token in &current_line_tokens {
-            match token {
-                Token::Number(number) => {
-                    println!(
-                        "Number found {} {} {}",
-                        number.value, number.start_index, number.number_len
-                    );
-                    let mut number_used = false;
-                    for symbol in &symbols {
-                        if *symbol == number.start_index - 1 {
-                            println!("Symbol before!");
-                            number_used = true;
-                            break;
-                        } else if *symbol == number.start_index + number.number_len {
-                            println!("Symbol after!");
-                            number_used = true;
-                            break;
-                        }
-                    }
-                    if !number_used {
-                        for symbol in &prev_symbols {
-                            let min_index = match number.start_index {
-                                0 => 0,
-                                _ => number.start_index - 1,
-                            };
-                            if (min_index <= *symbol)
-                                && (*symbol <= number.start_index + number.number_len)
-                            {
-                                println!("Symbol above!");
-                                number_used = true;
-                                break;
-                            }
-                        }
-                    }
+    schematic
+        .part_numbers()
+        .iter()
+        .map(|number| number.value)
+        .sum()
+}
 
-                    if number_used {
-                        println!("Number added to sum: {}", number.value);
-                        digits_sum += number.value;
-                    } else {
-                        numbers.push(Token::Number(number.clone()));
-                    }
-                }
-                Token::Symbol(symbol_idx) => {
-                    println!("Symbol found {}", symbol_idx);
-                    for number in &prev_numbers {
-                        match number {
-                            Token::Number(number) => {
-                                if used_numbers.contains(number) {
-                                    println!(
-                                        "Number {} {} {} already added",
-                                        number.value, number.start_index, number.number_len
-                                    );
-                                    continue;
-                                }
-                                println!(
-                                    "Processing number: {} {} {}",
-                                    number.value, number.start_index, number.number_len
-                                );
-                                let min_index = match number.start_index {
-                                    0 => 0,
-                                    _ => number.start_index - 1,
-                                };
-                                if (min_index <= *symbol_idx)
-                                    && (*symbol_idx <= number.start_index + number.number_len)
-                                {
-                                    println!("Number above!");
-                                    println!("Number added to sum: {}", number.value);
-                                    used_numbers.insert(number);
-                                    digits_sum += number.value;
-                                }
-                            }
-                            _ => (),
-                        }
-                    }
-                }
-                _ => (),
+pub fn solve_part_01(file_path: &Path) -> i64 {
+    sum_part_numbers(file_path, false)
+}
+
+pub fn solve_part_02(file_path: &Path) -> i64 {
+    let schematic = load_schematic(file_path, false);
+
+    gear_values(&schematic, '*', 2, Aggregate::Product)
+        .iter()
+        .map(|(_, _, value)| value)
+        .sum()
+}
+
+pub fn query_gears(file_path: &Path, symbol_value: char, arity: usize, aggregate: Aggregate) {
+    let schematic = load_schematic(file_path, false);
+
+    let mut total = 0;
+    for (symbol, numbers, value) in gear_values(&schematic, symbol_value, arity, aggregate) {
+        let numbers: Vec<_> = numbers.iter().map(|n| n.value.to_string()).collect();
+        println!(
+            "Symbol {} at {},{} numbers [{}] {:?}: {}",
+            symbol.value,
+            symbol.line_idx,
+            symbol.col_idx,
+            numbers.join(", "),
+            aggregate,
+            value
+        );
+        total += value;
+    }
+    println!("Total: {}", total);
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Marker {
+    PartNumber,
+    OtherNumber,
+    Gear,
+}
+
+const COLOR_GREEN: &str = "\x1b[32m";
+const COLOR_RED: &str = "\x1b[31m";
+const COLOR_YELLOW: &str = "\x1b[33m";
+const COLOR_RESET: &str = "\x1b[0m";
+
+fn schematic_markers(schematic: &Schematic) -> HashMap<(usize, usize), Marker> {
+    let mut markers = HashMap::new();
+    for number in &schematic.numbers {
+        let marker = match schematic.adjacent_symbols(number).is_empty() {
+            true => Marker::OtherNumber,
+            false => Marker::PartNumber,
+        };
+        for col_idx in number.start_index..number.start_index + number.number_len {
+            markers.insert((number.line_idx, col_idx), marker);
+        }
+    }
+    for (symbol, _) in schematic.gears('*', 2) {
+        markers.insert((symbol.line_idx, symbol.col_idx), Marker::Gear);
+    }
+    markers
+}
+
+// Schematic with part numbers in green, other numbers in red and gears in yellow
+fn render_colored(file_content: &str) -> String {
+    let markers = schematic_markers(&Schematic::from_str(file_content, false));
+    let mut rendered = String::new();
+
+    for (line_idx, line) in file_content.lines().enumerate() {
+        let mut current_color = None;
+        for (col_idx, char) in line.chars().enumerate() {
+            let color = markers
+                .get(&(line_idx, col_idx))
+                .map(|marker| match marker {
+                    Marker::PartNumber => COLOR_GREEN,
+                    Marker::OtherNumber => COLOR_RED,
+                    Marker::Gear => COLOR_YELLOW,
+                });
+            if color != current_color {
+                rendered += color.unwrap_or(COLOR_RESET);
+                current_color = color;
             }
+            rendered.push(char);
+        }
+        if current_color.is_some() {
+            rendered += COLOR_RESET;
         }
-        println!("Line :{} sum until now {}", line_idx, digits_sum);
-        prev_symbols = symbols;
-        prev_numbers = numbers;
+        rendered.push('\n');
     }
 
-    println!("Sum: {}", digits_sum);
+    rendered
 }
 
-fn push_symbol(
-    symbol: &DefinedSymbol,
-    number: &Number,
-    symbols_map: &mut HashMap<DefinedSymbol, Vec<Number>>,
-) {
-    if let Some(vec) = symbols_map.get_mut(symbol) {
-        vec.push(number.clone());
-    } else {
-        symbols_map.insert(symbol.clone(), vec![number.clone()]);
+// Schematic where every line is followed by a line marking the classification
+// of the characters above it, for terminals without colors
+fn render_plain(file_content: &str) -> String {
+    let markers = schematic_markers(&Schematic::from_str(file_content, false));
+    let mut rendered = String::from("Legend: P - part number, N - not a part number, G - gear\n");
+
+    for (line_idx, line) in file_content.lines().enumerate() {
+        let legend_line: String = (0..line.chars().count())
+            .map(|col_idx| match markers.get(&(line_idx, col_idx)) {
+                Some(Marker::PartNumber) => 'P',
+                Some(Marker::OtherNumber) => 'N',
+                Some(Marker::Gear) => 'G',
+                None => ' ',
+            })
+            .collect();
+        rendered += line;
+        rendered.push('\n');
+        rendered += legend_line.trim_end();
+        rendered.push('\n');
+    }
+
+    rendered
+}
+
+pub fn render_schematic(file_path: &Path, colored: bool) {
+    let file_content = fs::read_to_string(file_path).expect("File could not be loaded");
+    match colored {
+        true => print!("{}", render_colored(&file_content)),
+        false => print!("{}", render_plain(&file_content)),
     }
 }
 
-pub fn day03_task02() {
-    let input_filepath = match std::env::current_dir() {
-        Ok(filepath) => filepath.join("input_d03_t01"),
-        //Ok(filepath) => filepath.join("input_d03_test2"),
-        Err(_) => panic!("Cannot find current directory"),
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adjacency_on_ragged_lines() {
+        let schematic = Schematic::from_str("12\n..*...\n...34\n7", false);
+        let part_numbers: Vec<_> = schematic.part_numbers().iter().map(|n| n.value).collect();
+        assert_eq!(part_numbers, vec![12, 34]);
+    }
+
+    #[test]
+    fn test_tokenizer_columns_after_non_ascii_symbol() {
+        let schematic = Schematic::from_str("ä.12\n...€", false);
+        let part_numbers: Vec<_> = schematic.part_numbers().iter().map(|n| n.value).collect();
+        assert_eq!(part_numbers, vec![12]);
+        assert_eq!(schematic.numbers[0].start_index, 2);
+        assert_eq!(schematic.symbols[&(1, 3)].value, '€');
+    }
+
+    #[test]
+    fn test_signed_part_numbers() {
+        let schematic = Schematic::from_str("..-12..\n.....*.", true);
+        let part_numbers: Vec<_> = schematic.part_numbers().iter().map(|n| n.value).collect();
+        assert_eq!(part_numbers, vec![-12]);
+    }
 
-    println!("Input filepath: {}", input_filepath.display());
+    #[test]
+    fn test_gear_queries() {
+        let input_filepath = match std::env::current_dir() {
+            Ok(filepath) => filepath.join("input_d03_test"),
+            Err(_) => panic!("Cannot find current directory"),
+        };
+        let schematic = load_schematic(&input_filepath, false);
 
-    let file_content = fs::read_to_string(input_filepath).expect("File could not be loaded");
+        let sums: Vec<_> = gear_values(&schematic, '*', 2, Aggregate::Sum)
+            .iter()
+            .map(|(_, _, value)| *value)
+            .collect();
+        assert_eq!(sums, vec![502, 1353]);
 
-    let mut symbols_map: HashMap<DefinedSymbol, Vec<Number>> = HashMap::new();
-    let mut prev_symbols: Vec<DefinedSymbol> = Vec::new();
-    let mut prev_numbers = Vec::new();
-    let mut digits_sum: u64 = 0;
-    for (line_idx, engine_schematic_line) in file_content.split("\n").enumerate() {
-        let current_line_tokens = tokenizer(engine_schematic_line, line_idx);
-        let symbols: Vec<_> = current_line_tokens
+        let single: Vec<_> = gear_values(&schematic, '*', 1, Aggregate::Max)
             .iter()
-            .filter_map(|i| match *i {
-                Token::DefinedSymbol(symbol) => Some(symbol),
-                _ => None,
-            })
+            .map(|(symbol, _, value)| (symbol.line_idx, symbol.col_idx, *value))
             .collect();
-        let mut numbers: Vec<_> = Vec::new();
-        let mut used_numbers: HashSet<_> = HashSet::new();
-        for token in &current_line_tokens {
-            match token {
-                Token::Number(number) => {
-                    println!(
-                        "Number found {} {} {}",
-                        number.value, number.start_index, number.number_len
-                    );
-                    let mut number_used = false;
-                    for symbol in &symbols {
-                        if symbol.col_idx == number.start_index - 1 {
-                            println!("Symbol before!");
-                            number_used = true;
-                            push_symbol(symbol, number, &mut symbols_map);
-                            break;
-                        } else if symbol.col_idx == number.start_index + number.number_len {
-                            println!("Symbol after!");
-                            number_used = true;
-                            push_symbol(symbol, number, &mut symbols_map);
-                            break;
-                        }
-                    }
-                    if !number_used {
-                        for symbol in &prev_symbols {
-                            let min_index = match number.start_index {
-                                0 => 0,
-                                _ => number.start_index - 1,
-                            };
-                            if (min_index <= symbol.col_idx)
-                                && (symbol.col_idx <= number.start_index + number.number_len)
-                            {
-                                println!("Symbol above!");
-                                number_used = true;
-                                push_symbol(symbol, number, &mut symbols_map);
-                                break;
-                            }
-                        }
-                    }
+        assert_eq!(single, vec![(4, 3, 617)]);
 
-                    if number_used {
-                        println!("Number added to sum: {}", number.value);
-                        digits_sum += number.value;
-                    } else {
-                        numbers.push(Token::Number(number.clone()));
-                    }
-                }
-                Token::DefinedSymbol(symbol) => {
-                    println!("Symbol found {} {}", symbol.value, symbol.col_idx);
-                    for number in &prev_numbers {
-                        match number {
-                            Token::Number(number) => {
-                                if used_numbers.contains(number) {
-                                    println!(
-                                        "Number {} {} {} already added",
-                                        number.value, number.start_index, number.number_len
-                                    );
-                                    continue;
-                                }
-                                println!(
-                                    "Processing number: {} {} {}",
-                                    number.value, number.start_index, number.number_len
-                                );
-                                let min_index = match number.start_index {
-                                    0 => 0,
-                                    _ => number.start_index - 1,
-                                };
-                                if (min_index <= symbol.col_idx)
-                                    && (symbol.col_idx <= number.start_index + number.number_len)
-                                {
-                                    println!("Number above!");
-                                    println!("Number added to sum: {}", number.value);
-                                    used_numbers.insert(number);
-                                    push_symbol(symbol, number, &mut symbols_map);
-                                    digits_sum += number.value;
-                                }
-                            }
-                            _ => (),
-                        }
-                    }
-                }
-                _ => (),
-            }
-        }
-        println!("Line :{} sum until now {}", line_idx, digits_sum);
-        prev_symbols = symbols;
-        prev_numbers = numbers;
+        assert!(gear_values(&schematic, '#', 2, Aggregate::Product).is_empty());
+
+        let lonely = Schematic::from_str("1....\n.*..*\n", false);
+        let positions: Vec<_> = lonely
+            .gears('*', 0)
+            .iter()
+            .map(|(symbol, numbers)| (symbol.line_idx, symbol.col_idx, numbers.len()))
+            .collect();
+        assert_eq!(positions, vec![(1, 4, 0)]);
     }
 
-    let mut gear_ratios_sum = 0;
-    for (sym, values) in &symbols_map {
-        match sym {
-            DefinedSymbol {
-                value: '*',
-                line_idx,
-                col_idx,
-            } => {
-                println!(
-                    "Symbol {} {} has {} adjacents",
-                    line_idx,
-                    col_idx,
-                    values.len()
-                );
-                if values.len() == 2 {
-                    gear_ratios_sum += values[0].value * values[1].value;
-                }
-            }
-            _ => {}
-        }
+    #[test]
+    fn test_rendering_colored() {
+        let rendered = render_colored("467..114..\n...*......\n..35..633.");
+        let lines: Vec<_> = rendered.lines().collect();
+        assert_eq!(lines[0], "\x1b[32m467\x1b[0m..\x1b[31m114\x1b[0m..");
+        assert_eq!(lines[1], "...\x1b[33m*\x1b[0m......");
+        assert_eq!(lines[2], "..\x1b[32m35\x1b[0m..\x1b[31m633\x1b[0m.");
+    }
+
+    #[test]
+    fn test_rendering_plain() {
+        let rendered = render_plain("467..114..\n...*......\n..35..633.");
+        let lines: Vec<_> = rendered.lines().skip(1).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "467..114..",
+                "PPP  NNN",
+                "...*......",
+                "   G",
+                "..35..633.",
+                "  PP  NNN"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_solving_d03_part1() {
+        let input_filepath = match std::env::current_dir() {
+            Ok(filepath) => filepath.join("input_d03_test"),
+            Err(_) => panic!("Cannot find current directory"),
+        };
+        assert_eq!(solve_part_01(&input_filepath), 4361);
+    }
+
+    #[test]
+    fn test_solving_d03_part2() {
+        let input_filepath = match std::env::current_dir() {
+            Ok(filepath) => filepath.join("input_d03_test"),
+            Err(_) => panic!("Cannot find current directory"),
+        };
+        assert_eq!(solve_part_02(&input_filepath), 467835);
     }
-    println!("Gear ratios sum {}", gear_ratios_sum);
 }
@@ -1,105 +1,281 @@
-use std::{collections::HashMap, fs};
+use std::{collections::HashSet, fs, path::Path};
 
-fn parse_numbers(numbers_space_separated: &str) -> Vec<u64> {
-    let numbers: Vec<_> = numbers_space_separated
-        .split(" ")
-        .filter_map(|v| match v.parse::<u64>() {
-            Ok(i_val) => Some(i_val),
-            _ => None,
-        })
-        .collect();
-    numbers
+#[derive(Debug, PartialEq, Eq)]
+enum ScratchcardError {
+    InvalidLine(String),
+    DuplicatedNumber { card_id: usize, number: u64 },
+    OutOfOrderId { expected: usize, found: usize },
 }
 
-pub fn day04_task01() {
-    let input_filepath = match std::env::current_dir() {
-        Ok(filepath) => filepath.join("input_d04_t01"),
-        Err(_) => panic!("Cannot find current directory"),
-    };
+#[derive(Debug)]
+struct Scratchcard {
+    id: usize,
+    winning: HashSet<u64>,
+    owned: HashSet<u64>,
+}
+
+impl Scratchcard {
+    fn matches(&self) -> usize {
+        self.winning.intersection(&self.owned).count()
+    }
+}
+
+fn parse_card_id(card_name: &str) -> Option<usize> {
+    let splitted_name: Vec<_> = card_name.split_whitespace().collect();
+    match splitted_name.as_slice() {
+        ["Card", id] => id.parse::<usize>().ok(),
+        _ => None,
+    }
+}
+
+fn parse_numbers(
+    numbers_space_separated: &str,
+    card_id: usize,
+) -> Result<HashSet<u64>, ScratchcardError> {
+    let mut numbers = HashSet::new();
+    for number_str in numbers_space_separated.split_whitespace() {
+        let number = number_str
+            .parse::<u64>()
+            .map_err(|_| ScratchcardError::InvalidLine(numbers_space_separated.to_string()))?;
+        if !numbers.insert(number) {
+            return Err(ScratchcardError::DuplicatedNumber { card_id, number });
+        }
+    }
+    Ok(numbers)
+}
+
+fn parse_scratchcard(line: &str) -> Result<Scratchcard, ScratchcardError> {
+    let invalid_line = || ScratchcardError::InvalidLine(line.to_string());
+
+    let (card_name, values) = line.split_once(":").ok_or_else(invalid_line)?;
+    let id = parse_card_id(card_name).ok_or_else(invalid_line)?;
+    let (winning_values, owned_values) = values.split_once("|").ok_or_else(invalid_line)?;
+
+    Ok(Scratchcard {
+        id,
+        winning: parse_numbers(winning_values, id)?,
+        owned: parse_numbers(owned_values, id)?,
+    })
+}
 
-    println!("Input filepath: {}", input_filepath.display());
+fn parse_scratchcards(file_content: &str) -> Result<Vec<Scratchcard>, ScratchcardError> {
+    let mut scratchcards = Vec::new();
+    for line in file_content.lines().filter(|line| !line.trim().is_empty()) {
+        let scratchcard = parse_scratchcard(line)?;
+        let expected = scratchcards.len() + 1;
+        if scratchcard.id != expected {
+            return Err(ScratchcardError::OutOfOrderId {
+                expected,
+                found: scratchcard.id,
+            });
+        }
+        scratchcards.push(scratchcard);
+    }
+    Ok(scratchcards)
+}
 
-    let file_content = fs::read_to_string(input_filepath).expect("File could not be loaded");
+fn load_scratchcards(file_path: &Path) -> Vec<Scratchcard> {
+    let file_content = fs::read_to_string(file_path).expect("File could not be loaded");
+    parse_scratchcards(&file_content).expect("Invalid scratchcards")
+}
 
-    let splitted_content = file_content.split("\n");
+pub fn solve_part_01(file_path: &Path) -> u64 {
+    let scratchcards = load_scratchcards(file_path);
 
     let mut sum = 0;
-    for line in splitted_content {
-        let splitted_card: Vec<_> = line.split(":").collect();
-        if splitted_card.len() == 2 {
-            let (_card_name, values) = (splitted_card[0], splitted_card[1]);
-            let splitted_values: Vec<_> = values.split("|").collect();
-            if splitted_values.len() == 2 {
-                let winning_values = parse_numbers(splitted_values[0]);
-                let elf_values = parse_numbers(splitted_values[1]);
-                println!("Winning values: {}", winning_values.len());
-                let winning_elf_numbers: Vec<_> = winning_values
-                    .iter()
-                    .filter(|x| elf_values.contains(x))
-                    .collect();
-                println!("Winning elf values: {}", winning_elf_numbers.len());
-                sum += match winning_elf_numbers.len() {
-                    val if val > 0 => 2u64.pow(val as u32 - 1),
-                    _ => 0,
-                }
-            }
+    for scratchcard in &scratchcards {
+        let winning_elf_numbers = scratchcard.matches();
+        sum += match winning_elf_numbers {
+            val if val > 0 => 2u64.pow(val as u32 - 1),
+            _ => 0,
         }
     }
-    println!("Sum of winning powers: {}", sum);
-}
-
-pub fn day04_task02() {
-    let input_filepath = match std::env::current_dir() {
-        Ok(filepath) => filepath.join("input_d04_t01"),
-        Err(_) => panic!("Cannot find current directory"),
-    };
-
-    println!("Input filepath: {}", input_filepath.display());
-
-    let file_content = fs::read_to_string(input_filepath).expect("File could not be loaded");
-
-    let splitted_content = file_content.split("\n");
-
-    let mut scratchcards: HashMap<usize, u64> = HashMap::new();
-    for (idx, line) in splitted_content.enumerate() {
-        let card_idx = idx + 1;
-        let splitted_card: Vec<_> = line.split(":").collect();
-        if splitted_card.len() == 2 {
-            let (_card_name, values) = (splitted_card[0], splitted_card[1]);
-            let splitted_values: Vec<_> = values.split("|").collect();
-            if splitted_values.len() == 2 {
-                let winning_values = parse_numbers(splitted_values[0]);
-                let elf_values = parse_numbers(splitted_values[1]);
-                let scratchcards_amount = match scratchcards.get(&card_idx) {
-                    Some(val) => val + 1,
-                    _ => 1,
-                };
-                scratchcards.insert(card_idx, scratchcards_amount);
-                println!("Number of cards: {} {}", card_idx, scratchcards_amount);
-                let winning_elf_numbers: Vec<_> = winning_values
-                    .iter()
-                    .filter(|x| elf_values.contains(x))
-                    .collect();
-                let winning_cards = winning_elf_numbers.len();
-                println!("Winning elf values: {}", winning_cards);
-                for val in 0..winning_cards {
-                    let won_card_id = card_idx + val + 1;
-                    println!(
-                        "Increasing card {} number by: {}",
-                        won_card_id, scratchcards_amount
-                    );
-
-                    let scratchcards_amount = match scratchcards.get(&won_card_id) {
-                        Some(val) => val + scratchcards_amount,
-                        _ => scratchcards_amount,
-                    };
-                    scratchcards.insert(won_card_id, scratchcards_amount);
-                }
-            }
+    sum
+}
+
+// Result of processing scratchcards in order, where every instance of a card
+// wins one copy of each of the following `matches` cards
+struct Cascade {
+    // Total number of instances per card, index 0 is card 1
+    instances: Vec<u64>,
+    // For every card: ids of earlier cards which produced its copies and how many
+    provenance: Vec<Vec<(usize, u64)>>,
+    // Cards whose wins run past the last card: (card id, number of missing cards)
+    overflowing_wins: Vec<(usize, usize)>,
+}
+
+fn simulate_cascade(scratchcards: &[Scratchcard]) -> Cascade {
+    let cards_count = scratchcards.len();
+    let mut instances = vec![1; cards_count];
+    let mut provenance = vec![Vec::new(); cards_count];
+    let mut overflowing_wins = Vec::new();
+
+    for (card_idx, scratchcard) in scratchcards.iter().enumerate() {
+        let matches = scratchcard.matches();
+        let last_won_idx = card_idx + matches;
+        if last_won_idx >= cards_count {
+            overflowing_wins.push((scratchcard.id, last_won_idx + 1 - cards_count));
+        }
+        for won_card_idx in card_idx + 1..=last_won_idx.min(cards_count - 1) {
+            instances[won_card_idx] += instances[card_idx];
+            provenance[won_card_idx].push((scratchcard.id, instances[card_idx]));
+        }
+    }
+
+    Cascade {
+        instances,
+        provenance,
+        overflowing_wins,
+    }
+}
+
+fn cascade_table(cascade: &Cascade) -> String {
+    let mut table = format!("{:>6} {:>10}  {}\n", "card", "instances", "copies from");
+    for (card_idx, instances) in cascade.instances.iter().enumerate() {
+        let sources: Vec<_> = cascade.provenance[card_idx]
+            .iter()
+            .map(|(source_id, copies)| format!("{}x{}", source_id, copies))
+            .collect();
+        let row = format!(
+            "{:>6} {:>10}  {}",
+            card_idx + 1,
+            instances,
+            sources.join(", ")
+        );
+        table += row.trim_end();
+        table.push('\n');
+    }
+    for (card_id, missing_cards) in &cascade.overflowing_wins {
+        table += &format!(
+            "Card {} wins {} cards past the last card\n",
+            card_id, missing_cards
+        );
+    }
+    table
+}
+
+fn cascade_dot(cascade: &Cascade) -> String {
+    let mut dot = String::from("digraph scratchcards {\n");
+    for (card_idx, instances) in cascade.instances.iter().enumerate() {
+        dot += &format!(
+            "    {} [label=\"Card {}\\n{} instances\"];\n",
+            card_idx + 1,
+            card_idx + 1,
+            instances
+        );
+    }
+    for (card_idx, sources) in cascade.provenance.iter().enumerate() {
+        for (source_id, copies) in sources {
+            dot += &format!(
+                "    {} -> {} [label=\"{}\"];\n",
+                source_id,
+                card_idx + 1,
+                copies
+            );
         }
     }
-    println!(
-        "Sum of winning powers: {}",
-        scratchcards.values().fold(0, |total, x| total + x)
-    );
+    for (card_id, missing_cards) in &cascade.overflowing_wins {
+        dot += &format!(
+            "    {} -> past_last_card [label=\"{}\", style=dashed];\n",
+            card_id, missing_cards
+        );
+    }
+    dot += "}\n";
+    dot
+}
+
+pub fn solve_part_02(file_path: &Path) -> u64 {
+    let scratchcards = load_scratchcards(file_path);
+    simulate_cascade(&scratchcards).instances.iter().sum()
+}
+
+pub fn print_cascade(file_path: &Path, as_dot: bool) {
+    let cascade = simulate_cascade(&load_scratchcards(file_path));
+    match as_dot {
+        true => print!("{}", cascade_dot(&cascade)),
+        false => print!("{}", cascade_table(&cascade)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parsing_scratchcard() {
+        let scratchcard = parse_scratchcard("Card  12: 41 48 83 | 83 86  6 48").unwrap();
+        assert_eq!(scratchcard.id, 12);
+        assert_eq!(scratchcard.winning, HashSet::from([41, 48, 83]));
+        assert_eq!(scratchcard.matches(), 2);
+    }
+
+    #[test]
+    fn test_scratchcards_validation() {
+        assert_eq!(
+            parse_scratchcards("Card 1: 1 2 | 3 3").unwrap_err(),
+            ScratchcardError::DuplicatedNumber {
+                card_id: 1,
+                number: 3
+            }
+        );
+        assert_eq!(
+            parse_scratchcards("Card 1: 1 | 2\nCard 3: 1 | 2").unwrap_err(),
+            ScratchcardError::OutOfOrderId {
+                expected: 2,
+                found: 3
+            }
+        );
+        assert_eq!(
+            parse_scratchcards("Card 1: 1 2 3").unwrap_err(),
+            ScratchcardError::InvalidLine("Card 1: 1 2 3".to_string())
+        );
+    }
+
+    #[test]
+    fn test_cascade_provenance() {
+        let input_filepath = match std::env::current_dir() {
+            Ok(filepath) => filepath.join("input_d04_test"),
+            Err(_) => panic!("Cannot find current directory"),
+        };
+        let cascade = simulate_cascade(&load_scratchcards(&input_filepath));
+        assert_eq!(cascade.instances, vec![1, 2, 4, 8, 14, 1]);
+        assert_eq!(cascade.provenance[3], vec![(1, 1), (2, 2), (3, 4)]);
+        assert!(cascade.overflowing_wins.is_empty());
+    }
+
+    #[test]
+    fn test_cascade_wins_past_last_card() {
+        let scratchcards = parse_scratchcards("Card 1: 1 2 | 1 2\nCard 2: 3 4 5 | 3 4").unwrap();
+        let cascade = simulate_cascade(&scratchcards);
+        assert_eq!(cascade.instances, vec![1, 2]);
+        assert_eq!(cascade.overflowing_wins, vec![(1, 1), (2, 2)]);
+        assert_eq!(
+            cascade_dot(&cascade),
+            "digraph scratchcards {\n    \
+                1 [label=\"Card 1\\n1 instances\"];\n    \
+                2 [label=\"Card 2\\n2 instances\"];\n    \
+                1 -> 2 [label=\"1\"];\n    \
+                1 -> past_last_card [label=\"1\", style=dashed];\n    \
+                2 -> past_last_card [label=\"2\", style=dashed];\n\
+            }\n"
+        );
+    }
+
+    #[test]
+    fn test_solving_d04_part1() {
+        let input_filepath = match std::env::current_dir() {
+            Ok(filepath) => filepath.join("input_d04_test"),
+            Err(_) => panic!("Cannot find current directory"),
+        };
+        assert_eq!(solve_part_01(&input_filepath), 13);
+    }
+
+    #[test]
+    fn test_solving_d04_part2() {
+        let input_filepath = match std::env::current_dir() {
+            Ok(filepath) => filepath.join("input_d04_test"),
+            Err(_) => panic!("Cannot find current directory"),
+        };
+        assert_eq!(solve_part_02(&input_filepath), 30);
+    }
 }
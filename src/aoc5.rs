@@ -1,260 +1,958 @@
-use std::{collections::HashMap, fs, u64};
-
-fn find_in_map(map: &HashMap<(u64, u64), u64>, val: u64) -> u64 {
-    let found_val = val;
-    for ((range_lower, range_upper), dst_start) in map {
-        if *range_lower <= val && val < *range_upper {
-            //println!(
-            //    "Found value {} in ({}, {}]",
-            //    val, *range_lower, *range_upper
-            //);
-            let dst_val = dst_start + (val - *range_lower);
-            //println!("Assigning {}", dst_val);
-            return dst_val;
-        }
-    }
-    //println!("Not found, returning as is {}", found_val);
-    return found_val;
+use core::fmt;
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    path::Path,
+};
+
+// Entries of a map in almanac order: [source start, source end) => destination start
+type RangeMap = Vec<((u64, u64), u64)>;
+// List of [start, end) ranges
+type Ranges = Vec<(u64, u64)>;
+
+// Sorted, non-overlapping ranges covering the same values
+fn merge_ranges(mut ranges: Vec<(u64, u64)>) -> Vec<(u64, u64)> {
+    ranges.retain(|(start, end)| start < end);
+    ranges.sort();
+    let mut merged: Vec<(u64, u64)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = end.max(*last_end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
 }
 
-pub fn day05_task01() {
-    let input_filepath = match std::env::current_dir() {
-        Ok(filepath) => filepath.join("input_d05_t01"),
-        //Ok(filepath) => filepath.join("input_d05_test"),
-        Err(_) => panic!("Cannot find current directory"),
+fn intersect_ranges(first: &[(u64, u64)], second: &[(u64, u64)]) -> Vec<(u64, u64)> {
+    let mut intersection = Vec::new();
+    for (first_start, first_end) in first {
+        for (second_start, second_end) in second {
+            intersection.push((*first_start.max(second_start), *first_end.min(second_end)));
+        }
+    }
+    merge_ranges(intersection)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum MapIssue {
+    // Two source ranges share some values, so the result depends on lookup order
+    Overlap {
+        first: (u64, u64),
+        second: (u64, u64),
+    },
+    // Values between source ranges which are mapped to themselves
+    Gap {
+        start: u64,
+        end: u64,
+    },
+}
+
+impl fmt::Display for MapIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapIssue::Overlap { first, second } => write!(
+                f,
+                "overlapping source ranges [{}, {}) and [{}, {})",
+                first.0, first.1, second.0, second.1
+            ),
+            MapIssue::Gap { start, end } => write!(f, "gap [{}, {})", start, end),
+        }
+    }
+}
+
+fn validate_map(map: &RangeMap) -> Vec<MapIssue> {
+    let mut source_ranges: Vec<_> = map.iter().map(|(source_range, _)| *source_range).collect();
+    source_ranges.sort();
+
+    let mut issues = Vec::new();
+    for (idx, first) in source_ranges.iter().enumerate() {
+        for second in source_ranges[idx + 1..].iter() {
+            if second.0 >= first.1 {
+                break;
+            }
+            issues.push(MapIssue::Overlap {
+                first: *first,
+                second: *second,
+            });
+        }
+    }
+
+    let mut covered_until = match source_ranges.first() {
+        Some((start, _)) => *start,
+        None => 0,
     };
+    for (start, end) in source_ranges {
+        if start > covered_until {
+            issues.push(MapIssue::Gap {
+                start: covered_until,
+                end: start,
+            });
+        }
+        covered_until = covered_until.max(end);
+    }
+    issues
+}
 
-    println!("Input filepath: {}", input_filepath.display());
+// Values in [start, end) are mapped to [dst_start, dst_start + end - start)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Segment {
+    start: u64,
+    end: u64,
+    dst_start: u64,
+}
 
-    let file_content = fs::read_to_string(input_filepath).expect("File could not be loaded");
+// Function defined by sorted, non-overlapping segments covering [0, u64::MAX)
+#[derive(Debug, PartialEq, Eq)]
+struct PiecewiseLinear {
+    segments: Vec<Segment>,
+}
 
-    let mut seeds: Vec<u64> = Vec::new();
-    let mut seed_to_soil: HashMap<(u64, u64), u64> = HashMap::new();
-    let mut soil_to_fertilizer: HashMap<(u64, u64), u64> = HashMap::new();
-    let mut fertilizer_to_water: HashMap<(u64, u64), u64> = HashMap::new();
-    let mut water_to_light: HashMap<(u64, u64), u64> = HashMap::new();
-    let mut light_to_temperature: HashMap<(u64, u64), u64> = HashMap::new();
-    let mut temperature_to_humidity: HashMap<(u64, u64), u64> = HashMap::new();
-    let mut humidity_to_location: HashMap<(u64, u64), u64> = HashMap::new();
+impl PiecewiseLinear {
+    // Overlapping source ranges are resolved in favour of the lower range start,
+    // stages with overlaps refuse to use the function though
+    fn from_map(map: &RangeMap) -> PiecewiseLinear {
+        let mut entries: Vec<_> = map.iter().collect();
+        entries.sort();
 
-    let mut current_map = &mut seed_to_soil;
+        let mut segments = Vec::new();
+        let mut covered_until = 0;
+        for ((range_lower, range_upper), dst_start) in entries {
+            let start = (*range_lower).max(covered_until);
+            if start >= *range_upper {
+                continue;
+            }
+            segments.push(Segment {
+                start: covered_until,
+                end: start,
+                dst_start: covered_until,
+            });
+            segments.push(Segment {
+                start,
+                end: *range_upper,
+                dst_start: dst_start + (start - range_lower),
+            });
+            covered_until = *range_upper;
+        }
+        segments.push(Segment {
+            start: covered_until,
+            end: u64::MAX,
+            dst_start: covered_until,
+        });
+
+        PiecewiseLinear::from_segments(segments)
+    }
+
+    // Drops empty segments and joins neighbours continuing the same shift
+    fn from_segments(segments: Vec<Segment>) -> PiecewiseLinear {
+        let mut merged: Vec<Segment> = Vec::new();
+        for segment in segments.into_iter().filter(|s| s.start < s.end) {
+            match merged.last_mut() {
+                Some(last)
+                    if last.end == segment.start
+                        && last.dst_start + (last.end - last.start) == segment.dst_start =>
+                {
+                    last.end = segment.end;
+                }
+                _ => merged.push(segment),
+            }
+        }
+        PiecewiseLinear { segments: merged }
+    }
+
+    fn evaluate(&self, value: u64) -> u64 {
+        let idx = self
+            .segments
+            .partition_point(|segment| segment.end <= value);
+        match self.segments.get(idx) {
+            Some(segment) if segment.start <= value => segment.dst_start + (value - segment.start),
+            _ => value,
+        }
+    }
 
-    for line in file_content.split("\n") {
-        if line.starts_with("seeds:") {
-            let seeds_splitted: Vec<_> = line.split(":").collect();
-            if seeds_splitted.len() == 2 {
-                let seeds_string = seeds_splitted[1];
-                for seed_nr_string in seeds_string.split(" ") {
+    // Pushes whole [start, end) ranges through the function, splitting them on
+    // segment boundaries
+    fn map_ranges(&self, ranges: &[(u64, u64)]) -> Ranges {
+        let mut mapped = Vec::new();
+        for (start, end) in ranges.iter().filter(|(start, end)| start < end) {
+            let first_idx = self
+                .segments
+                .partition_point(|segment| segment.end <= *start);
+            for segment in &self.segments[first_idx..] {
+                if segment.start >= *end {
+                    break;
+                }
+                let overlap = ((*start).max(segment.start), (*end).min(segment.end));
+                mapped.push((
+                    segment.dst_start + (overlap.0 - segment.start),
+                    segment.dst_start + (overlap.1 - segment.start),
+                ));
+            }
+        }
+        mapped
+    }
+
+    // All values which the function sends into given ranges
+    fn preimage(&self, ranges: &[(u64, u64)]) -> Ranges {
+        let mut preimage = Vec::new();
+        for (start, end) in ranges {
+            for segment in &self.segments {
+                let dst_end = segment.dst_start + (segment.end - segment.start);
+                let overlap = ((*start).max(segment.dst_start), (*end).min(dst_end));
+                if overlap.0 < overlap.1 {
+                    preimage.push((
+                        segment.start + (overlap.0 - segment.dst_start),
+                        segment.start + (overlap.1 - segment.dst_start),
+                    ));
+                }
+            }
+        }
+        merge_ranges(preimage)
+    }
+
+    // Applies `self` first and `next` on its results
+    fn then(&self, next: &PiecewiseLinear) -> PiecewiseLinear {
+        let mut segments = Vec::new();
+        for segment in &self.segments {
+            let dst_end = segment.dst_start + (segment.end - segment.start);
+            let first_idx = next
+                .segments
+                .partition_point(|next_segment| next_segment.end <= segment.dst_start);
+            for next_segment in &next.segments[first_idx..] {
+                if next_segment.start >= dst_end {
+                    break;
+                }
+                let overlap = (
+                    segment.dst_start.max(next_segment.start),
+                    dst_end.min(next_segment.end),
+                );
+                segments.push(Segment {
+                    start: segment.start + (overlap.0 - segment.dst_start),
+                    end: segment.start + (overlap.1 - segment.dst_start),
+                    dst_start: next_segment.dst_start + (overlap.0 - next_segment.start),
+                });
+            }
+        }
+        PiecewiseLinear::from_segments(segments)
+    }
+
+    // Non-identity segments in the almanac map format, so it can be parsed back
+    fn serialize(&self, source: &str, destination: &str) -> String {
+        let mut serialized = format!("{}-to-{} map:\n", source, destination);
+        for segment in &self.segments {
+            if segment.start != segment.dst_start {
+                serialized += &format!(
+                    "{} {} {}\n",
+                    segment.dst_start,
+                    segment.start,
+                    segment.end - segment.start
+                );
+            }
+        }
+        serialized
+    }
+}
+
+impl fmt::Display for PiecewiseLinear {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for segment in &self.segments {
+            let offset = segment.dst_start as i128 - segment.start as i128;
+            writeln!(f, "[{}, {}) {:+}", segment.start, segment.end, offset)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum AlmanacError {
+    InvalidLine(String),
+    NoPath {
+        from: String,
+        to: String,
+    },
+    OverlappingRanges {
+        stage: String,
+        first: (u64, u64),
+        second: (u64, u64),
+    },
+    UnpairedSeed(u64),
+    SeedRangeOverflow {
+        start: u64,
+        len: u64,
+    },
+}
+
+impl fmt::Display for AlmanacError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlmanacError::InvalidLine(line) => write!(f, "Cannot parse line '{}'", line),
+            AlmanacError::NoPath { from, to } => {
+                write!(f, "There is no way to convert {} to {}", from, to)
+            }
+            AlmanacError::OverlappingRanges {
+                stage,
+                first,
+                second,
+            } => write!(
+                f,
+                "{} map has overlapping source ranges [{}, {}) and [{}, {})",
+                stage, first.0, first.1, second.0, second.1
+            ),
+            AlmanacError::UnpairedSeed(seed) => {
+                write!(f, "Seed {} has no range length", seed)
+            }
+            AlmanacError::SeedRangeOverflow { start, len } => write!(
+                f,
+                "Seed range starting at {} with length {} runs past u64::MAX",
+                start, len
+            ),
+        }
+    }
+}
+
+// Map entries as written in the almanac, and the function they define which
+// every lookup goes through
+struct Stage {
+    source: String,
+    destination: String,
+    map: RangeMap,
+    function: PiecewiseLinear,
+    // First pair of overlapping source ranges, which leaves the function
+    // ambiguous
+    overlap: Option<((u64, u64), (u64, u64))>,
+}
+
+impl Stage {
+    fn new(source: String, destination: String, map: RangeMap) -> Stage {
+        let overlap = validate_map(&map)
+            .into_iter()
+            .find_map(|issue| match issue {
+                MapIssue::Overlap { first, second } => Some((first, second)),
+                MapIssue::Gap { .. } => None,
+            });
+        Stage {
+            function: PiecewiseLinear::from_map(&map),
+            source,
+            destination,
+            map,
+            overlap,
+        }
+    }
+
+    fn name(&self) -> String {
+        format!("{}-to-{}", self.source, self.destination)
+    }
+
+    // Function of the stage, unless overlapping source ranges make it ambiguous
+    fn function(&self) -> Result<&PiecewiseLinear, AlmanacError> {
+        match self.overlap {
+            Some((first, second)) => Err(AlmanacError::OverlappingRanges {
+                stage: self.name(),
+                first,
+                second,
+            }),
+            None => Ok(&self.function),
+        }
+    }
+}
+
+struct Almanac {
+    seeds: Vec<u64>,
+    stages: Vec<Stage>,
+}
+
+fn parse_map_header(line: &str) -> Option<(&str, &str)> {
+    let categories = line.strip_suffix(" map:")?;
+    categories.split_once("-to-")
+}
+
+// Exactly three numbers: destination range start, source range start and
+// range length, neither range may run past u64::MAX
+fn parse_map_entry(line: &str) -> Option<((u64, u64), u64)> {
+    let fields: Vec<_> = line.split_whitespace().collect();
+    let [destination_range_start, source_range_start, range_len] = fields.as_slice() else {
+        return None;
+    };
+    let destination_range_start = destination_range_start.parse::<u64>().ok()?;
+    let source_range_start = source_range_start.parse::<u64>().ok()?;
+    let range_len = range_len.parse::<u64>().ok()?;
+    destination_range_start.checked_add(range_len)?;
+    Some((
+        (
+            source_range_start,
+            source_range_start.checked_add(range_len)?,
+        ),
+        destination_range_start,
+    ))
+}
+
+impl Almanac {
+    fn from_str(file_content: &str) -> Result<Almanac, AlmanacError> {
+        let mut seeds = Vec::new();
+        let mut maps: Vec<(String, String, RangeMap)> = Vec::new();
+
+        for line in file_content.lines().map(|line| line.trim()) {
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(seeds_string) = line.strip_prefix("seeds:") {
+                for seed_nr_string in seeds_string.split_whitespace() {
                     match seed_nr_string.parse::<u64>() {
                         Ok(val) => seeds.push(val),
-                        Err(_) => (),
+                        Err(_) => return Err(AlmanacError::InvalidLine(line.to_string())),
                     }
                 }
+                continue;
+            }
+            if let Some((source, destination)) = parse_map_header(line) {
+                maps.push((source.to_string(), destination.to_string(), RangeMap::new()));
+                continue;
+            }
+            match (maps.last_mut(), parse_map_entry(line)) {
+                (Some((_, _, map)), Some(entry)) => map.push(entry),
+                _ => return Err(AlmanacError::InvalidLine(line.to_string())),
             }
-            continue;
         }
-        if line.starts_with("seed-to-soil") {
-            current_map = &mut seed_to_soil;
-            continue;
+
+        let stages = maps
+            .into_iter()
+            .map(|(source, destination, map)| Stage::new(source, destination, map))
+            .collect();
+        Ok(Almanac { seeds, stages })
+    }
+
+    // Shortest chain of stages converting `from` category to `to` category
+    fn path(&self, from: &str, to: &str) -> Result<Vec<&Stage>, AlmanacError> {
+        let mut previous_stage: HashMap<&str, Option<&Stage>> = HashMap::from([(from, None)]);
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(category) = queue.pop_front() {
+            if category == to {
+                let mut path = Vec::new();
+                let mut current = to;
+                while let Some(Some(stage)) = previous_stage.get(current) {
+                    path.push(*stage);
+                    current = &stage.source;
+                }
+                path.reverse();
+                return Ok(path);
+            }
+            for stage in self.stages.iter().filter(|stage| stage.source == category) {
+                if !previous_stage.contains_key(stage.destination.as_str()) {
+                    previous_stage.insert(&stage.destination, Some(stage));
+                    queue.push_back(&stage.destination);
+                }
+            }
         }
-        if line.starts_with("soil-to-fertilizer") {
-            current_map = &mut soil_to_fertilizer;
-            continue;
+
+        Err(AlmanacError::NoPath {
+            from: from.to_string(),
+            to: to.to_string(),
+        })
+    }
+
+    fn convert(&self, from: &str, to: &str, value: u64) -> Result<u64, AlmanacError> {
+        let mut found = value;
+        for stage in self.path(from, to)? {
+            found = stage.function()?.evaluate(found);
         }
-        if line.starts_with("fertilizer-to-water") {
-            current_map = &mut fertilizer_to_water;
-            continue;
+        Ok(found)
+    }
+
+    fn convert_ranges(
+        &self,
+        from: &str,
+        to: &str,
+        ranges: &[(u64, u64)],
+    ) -> Result<Vec<(u64, u64)>, AlmanacError> {
+        let mut found = ranges.to_vec();
+        for stage in self.path(from, to)? {
+            found = stage.function()?.map_ranges(&found);
         }
-        if line.starts_with("water-to-light") {
-            current_map = &mut water_to_light;
-            continue;
+        Ok(found)
+    }
+
+    // Value in every category on the way from `from` to `to`
+    fn chain(&self, from: &str, to: &str, value: u64) -> Result<Vec<(String, u64)>, AlmanacError> {
+        let mut chain = vec![(from.to_string(), value)];
+        for stage in self.path(from, to)? {
+            let found = stage.function()?.evaluate(chain[chain.len() - 1].1);
+            chain.push((stage.destination.clone(), found));
         }
-        if line.starts_with("light-to-temperature") {
-            current_map = &mut light_to_temperature;
-            continue;
+        Ok(chain)
+    }
+
+    // Ranges of `to` category followed by their preimages in every category
+    // on the way back to `from`
+    fn preimage_ranges(
+        &self,
+        from: &str,
+        to: &str,
+        ranges: &[(u64, u64)],
+    ) -> Result<Vec<(String, Ranges)>, AlmanacError> {
+        let mut preimages = vec![(to.to_string(), merge_ranges(ranges.to_vec()))];
+        for stage in self.path(from, to)?.iter().rev() {
+            let found = stage
+                .function()?
+                .preimage(&preimages[preimages.len() - 1].1);
+            preimages.push((stage.source.clone(), found));
         }
-        if line.starts_with("temperature-to-humidity") {
-            current_map = &mut temperature_to_humidity;
-            continue;
+        Ok(preimages)
+    }
+
+    // Issues found in every stage, stages named as in the almanac
+    fn validate(&self) -> Vec<(String, MapIssue)> {
+        let mut issues = Vec::new();
+        for stage in &self.stages {
+            let stage_name = stage.name();
+            for issue in validate_map(&stage.map) {
+                issues.push((stage_name.clone(), issue));
+            }
         }
+        issues
+    }
 
-        if line.starts_with("humidity-to-location") {
-            current_map = &mut humidity_to_location;
-            continue;
+    // Single function doing all conversions on the way from `from` to `to`
+    fn compose(&self, from: &str, to: &str) -> Result<PiecewiseLinear, AlmanacError> {
+        let mut composed = PiecewiseLinear::from_map(&RangeMap::new());
+        for stage in self.path(from, to)? {
+            composed = composed.then(stage.function()?);
         }
+        Ok(composed)
+    }
 
-        let unparsed_numbers: Vec<_> = line.split(" ").collect();
+    // Searches `to` values from the lowest upwards until one of them comes from
+    // `from_ranges`. Windows of growing size are checked first, then the window
+    // containing the answer is narrowed down by halving it.
+    fn lowest_reachable(
+        &self,
+        from: &str,
+        to: &str,
+        from_ranges: &[(u64, u64)],
+    ) -> Result<Option<u64>, AlmanacError> {
+        let from_ranges = merge_ranges(from_ranges.to_vec());
+        let comes_from_ranges = |start: u64, end: u64| -> Result<bool, AlmanacError> {
+            let preimages = self.preimage_ranges(from, to, &[(start, end)])?;
+            let preimage = &preimages[preimages.len() - 1].1;
+            Ok(!intersect_ranges(preimage, &from_ranges).is_empty())
+        };
 
-        if unparsed_numbers.len() == 3 {
-            let (destination_range_start_str, source_range_start_str, range_len_str) = (
-                unparsed_numbers[0],
-                unparsed_numbers[1],
-                unparsed_numbers[2],
-            );
+        let (mut window_start, mut window_len) = (0u64, 1u64);
+        let mut window_end = window_start + window_len;
+        while !comes_from_ranges(window_start, window_end)? {
+            if window_end == u64::MAX {
+                return Ok(None);
+            }
+            window_start = window_end;
+            window_len = window_len.saturating_mul(2);
+            window_end = window_start.saturating_add(window_len);
+        }
 
-            match destination_range_start_str.parse::<u64>() {
-                Ok(destinaton_range_start) => match source_range_start_str.parse::<u64>() {
-                    Ok(source_range_start) => match range_len_str.parse::<u64>() {
-                        Ok(range_len) => {
-                            println!(
-                                "Putting ({}, {}) => {} match",
-                                source_range_start,
-                                source_range_start + range_len,
-                                destinaton_range_start
-                            );
-                            current_map.insert(
-                                (source_range_start, source_range_start + range_len),
-                                destinaton_range_start,
-                            );
-                        }
-                        Err(_) => println!("Cannot parse {}", range_len_str),
-                    },
-                    Err(_) => println!("Cannot parse {}", source_range_start_str),
-                },
-                Err(_) => println!("Cannot parse {}", destination_range_start_str),
+        while window_end - window_start > 1 {
+            let middle = window_start + (window_end - window_start) / 2;
+            if comes_from_ranges(window_start, middle)? {
+                window_end = middle;
+            } else {
+                window_start = middle;
             }
         }
+        Ok(Some(window_start))
     }
+}
 
-    let mut locations: Vec<_> = Vec::new();
-    for s in seeds {
-        let mut found = find_in_map(&seed_to_soil, s);
-        found = find_in_map(&soil_to_fertilizer, found);
-        found = find_in_map(&fertilizer_to_water, found);
-        found = find_in_map(&water_to_light, found);
-        found = find_in_map(&light_to_temperature, found);
-        found = find_in_map(&temperature_to_humidity, found);
-        found = find_in_map(&humidity_to_location, found);
-        locations.push(found);
+fn load_almanac(file_path: &Path) -> Almanac {
+    let file_content = fs::read_to_string(file_path).expect("File could not be loaded");
+    match Almanac::from_str(&file_content) {
+        Ok(almanac) => almanac,
+        Err(err) => panic!("{}", err),
     }
+}
 
-    let min_location = match locations.iter().min() {
-        Some(val) => *val,
-        None => 0,
-    };
-    println!("Minimal location: {}", min_location);
+// Seeds line read as pairs of range start and range length, empty ranges
+// are left out
+fn seed_ranges(seeds: &[u64]) -> Result<Vec<(u64, u64)>, AlmanacError> {
+    if seeds.len() % 2 == 1 {
+        return Err(AlmanacError::UnpairedSeed(seeds[seeds.len() - 1]));
+    }
+    let mut ranges = Vec::new();
+    for pair in seeds.chunks_exact(2) {
+        let (start, len) = (pair[0], pair[1]);
+        let end = start
+            .checked_add(len)
+            .ok_or(AlmanacError::SeedRangeOverflow { start, len })?;
+        if start < end {
+            ranges.push((start, end));
+        }
+    }
+    Ok(ranges)
 }
 
-pub fn day05_task02() {
-    let input_filepath = match std::env::current_dir() {
-        Ok(filepath) => filepath.join("input_d05_t01"),
-        //Ok(filepath) => filepath.join("input_d05_test"),
-        Err(_) => panic!("Cannot find current directory"),
+pub fn solve_part_01(file_path: &Path) -> u64 {
+    let almanac = load_almanac(file_path);
+    let seed_to_location = match almanac.compose("seed", "location") {
+        Ok(composed) => composed,
+        Err(err) => panic!("{}", err),
     };
 
-    println!("Input filepath: {}", input_filepath.display());
+    almanac
+        .seeds
+        .iter()
+        .map(|s| seed_to_location.evaluate(*s))
+        .min()
+        .unwrap_or_default()
+}
 
-    let file_content = fs::read_to_string(input_filepath).expect("File could not be loaded");
+pub fn solve_part_02(file_path: &Path) -> u64 {
+    let almanac = load_almanac(file_path);
 
-    let mut seeds: Vec<(u64, u64)> = Vec::new();
-    let mut seed_to_soil: HashMap<(u64, u64), u64> = HashMap::new();
-    let mut soil_to_fertilizer: HashMap<(u64, u64), u64> = HashMap::new();
-    let mut fertilizer_to_water: HashMap<(u64, u64), u64> = HashMap::new();
-    let mut water_to_light: HashMap<(u64, u64), u64> = HashMap::new();
-    let mut light_to_temperature: HashMap<(u64, u64), u64> = HashMap::new();
-    let mut temperature_to_humidity: HashMap<(u64, u64), u64> = HashMap::new();
-    let mut humidity_to_location: HashMap<(u64, u64), u64> = HashMap::new();
+    let ranges = seed_ranges(&almanac.seeds)
+        .and_then(|seeds| almanac.convert_ranges("seed", "location", &seeds));
+    let ranges = match ranges {
+        Ok(ranges) => ranges,
+        Err(err) => panic!("{}", err),
+    };
 
-    let mut current_map = &mut seed_to_soil;
+    match ranges.iter().map(|(start, _)| start).min() {
+        Some(val) => *val,
+        None => u64::MAX,
+    }
+}
 
-    for line in file_content.split("\n") {
-        if line.starts_with("seeds:") {
-            let seeds_splitted: Vec<_> = line.split(":").collect();
-            if seeds_splitted.len() == 2 {
-                let seeds_string = seeds_splitted[1];
-                let mut seeds_tmp: Vec<_> = Vec::new();
-                for seed_nr_string in seeds_string.split(" ") {
-                    match seed_nr_string.parse::<u64>() {
-                        Ok(val) => seeds_tmp.push(val),
-                        Err(_) => (),
-                    }
-                    if seeds_tmp.len() == 2 {
-                        seeds.push((seeds_tmp[0], seeds_tmp[0] + seeds_tmp[1]));
-                        seeds_tmp.clear();
-                    }
-                }
+pub fn convert_value(file_path: &Path, from: &str, to: &str, value: u64) {
+    let almanac = load_almanac(file_path);
+    match almanac.convert(from, to, value) {
+        Ok(converted) => println!("{} {} => {} {}", from, value, to, converted),
+        Err(err) => println!("{}", err),
+    }
+}
+
+pub fn print_preimage(file_path: &Path, location_start: u64, location_end: u64) {
+    let almanac = load_almanac(file_path);
+    match almanac.preimage_ranges("seed", "location", &[(location_start, location_end)]) {
+        Ok(preimages) => {
+            for (category, ranges) in preimages {
+                let ranges: Vec<_> = ranges
+                    .iter()
+                    .map(|(start, end)| format!("[{}, {})", start, end))
+                    .collect();
+                println!("{:>12}: {}", category, ranges.join(" "));
             }
-            continue;
         }
-        if line.starts_with("seed-to-soil") {
-            current_map = &mut seed_to_soil;
-            continue;
-        }
-        if line.starts_with("soil-to-fertilizer") {
-            current_map = &mut soil_to_fertilizer;
-            continue;
+        Err(err) => println!("{}", err),
+    }
+}
+
+// Finds the lowest location of seed ranges searching from location 0 upwards
+// and prints how the seed gets there
+pub fn explain_lowest_location(file_path: &Path) {
+    let almanac = load_almanac(file_path);
+    let seeds = match seed_ranges(&almanac.seeds) {
+        Ok(seeds) => seeds,
+        Err(err) => {
+            println!("{}", err);
+            return;
         }
-        if line.starts_with("fertilizer-to-water") {
-            current_map = &mut fertilizer_to_water;
-            continue;
+    };
+
+    let location = match almanac.lowest_reachable("seed", "location", &seeds) {
+        Ok(Some(location)) => location,
+        Ok(None) => {
+            println!("No location can be reached from seeds");
+            return;
         }
-        if line.starts_with("water-to-light") {
-            current_map = &mut water_to_light;
-            continue;
+        Err(err) => {
+            println!("{}", err);
+            return;
         }
-        if line.starts_with("light-to-temperature") {
-            current_map = &mut light_to_temperature;
-            continue;
+    };
+
+    let seed = match almanac.preimage_ranges("seed", "location", &[(location, location + 1)]) {
+        Ok(preimages) => intersect_ranges(&preimages[preimages.len() - 1].1, &seeds)[0].0,
+        Err(err) => panic!("{}", err),
+    };
+    match almanac.chain("seed", "location", seed) {
+        Ok(chain) => {
+            let chain: Vec<_> = chain
+                .iter()
+                .map(|(category, value)| format!("{} {}", category, value))
+                .collect();
+            println!("{}", chain.join(" -> "));
         }
-        if line.starts_with("temperature-to-humidity") {
-            current_map = &mut temperature_to_humidity;
-            continue;
+        Err(err) => panic!("{}", err),
+    }
+}
+
+pub fn validate_almanac(file_path: &Path) {
+    let almanac = load_almanac(file_path);
+    let issues = almanac.validate();
+    for (stage_name, issue) in &issues {
+        println!("{}: {}", stage_name, issue);
+    }
+    println!("Found {} issues", issues.len());
+}
+
+pub fn print_composed(file_path: &Path, serialize: bool) {
+    let almanac = load_almanac(file_path);
+    match almanac.compose("seed", "location") {
+        Ok(composed) if serialize => print!("{}", composed.serialize("seed", "location")),
+        Ok(composed) => print!("{}", composed),
+        Err(err) => println!("{}", err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load_test_almanac() -> Almanac {
+        let input_filepath = match std::env::current_dir() {
+            Ok(filepath) => filepath.join("input_d05_test"),
+            Err(_) => panic!("Cannot find current directory"),
+        };
+        load_almanac(&input_filepath)
+    }
+
+    fn lowest_location_point_wise(almanac: &Almanac, seeds: &[u64]) -> u64 {
+        let mut lowest_location = u64::MAX;
+        for (seed_range_start, seed_range_end) in seed_ranges(seeds).unwrap() {
+            for i in seed_range_start..seed_range_end {
+                let found = almanac.convert("seed", "location", i).unwrap();
+                lowest_location = lowest_location.min(found);
+            }
         }
+        lowest_location
+    }
+
+    #[test]
+    fn test_seed_ranges() {
+        assert_eq!(seed_ranges(&[79, 14, 55, 0]), Ok(vec![(79, 93)]));
+        assert_eq!(
+            seed_ranges(&[79, 14, 55]),
+            Err(AlmanacError::UnpairedSeed(55))
+        );
+        assert_eq!(
+            seed_ranges(&[u64::MAX, 1]),
+            Err(AlmanacError::SeedRangeOverflow {
+                start: u64::MAX,
+                len: 1
+            })
+        );
+        let function = PiecewiseLinear::from_map(&vec![((10, 20), 100)]);
+        assert!(function.map_ranges(&[(15, 15), (30, 20)]).is_empty());
+    }
+
+    #[test]
+    fn test_splitting_ranges() {
+        let function = PiecewiseLinear::from_map(&vec![((10, 20), 100), ((30, 35), 0)]);
+        let mut ranges = function.map_ranges(&[(5, 32), (40, 45)]);
+        ranges.sort();
+        assert_eq!(
+            ranges,
+            vec![(0, 2), (5, 10), (20, 30), (40, 45), (100, 110)]
+        );
+    }
 
-        if line.starts_with("humidity-to-location") {
-            current_map = &mut humidity_to_location;
-            continue;
+    #[test]
+    fn test_ranges_match_point_wise_mapping() {
+        let almanac = load_test_almanac();
+
+        for seeds in [
+            vec![79, 14, 55, 13],
+            vec![0, 100],
+            vec![97, 3, 14, 1, 50, 1],
+        ] {
+            let ranges = almanac
+                .convert_ranges("seed", "location", &seed_ranges(&seeds).unwrap())
+                .unwrap();
+            let lowest_location = ranges.iter().map(|(start, _)| *start).min().unwrap();
+            assert_eq!(
+                lowest_location,
+                lowest_location_point_wise(&almanac, &seeds)
+            );
+
+            let ranges_len: u64 = ranges.iter().map(|(start, end)| end - start).sum();
+            let seeds_len: u64 = seeds.chunks_exact(2).map(|pair| pair[1]).sum();
+            assert_eq!(ranges_len, seeds_len);
         }
+    }
+
+    #[test]
+    fn test_converting_between_categories() {
+        let almanac = load_test_almanac();
+        assert_eq!(almanac.stages.len(), 7);
+        // Seed 79 -> soil 81 -> fertilizer 81 -> water 81 -> light 74
+        assert_eq!(almanac.convert("soil", "light", 81), Ok(74));
+        assert_eq!(almanac.convert("seed", "seed", 79), Ok(79));
+        assert_eq!(almanac.convert("seed", "location", 79), Ok(82));
+        assert_eq!(
+            almanac.convert("light", "soil", 74),
+            Err(AlmanacError::NoPath {
+                from: "light".to_string(),
+                to: "soil".to_string()
+            })
+        );
+    }
 
-        let unparsed_numbers: Vec<_> = line.split(" ").collect();
+    #[test]
+    fn test_parsing_arbitrary_stages() {
+        let almanac = Almanac::from_str(
+            "seeds: 1 2\n\na-to-b map:\n10 0 5\n\na-to-c map:\n20 0 5\n\nc-to-d map:\n0 20 1\n",
+        )
+        .unwrap();
+        assert_eq!(almanac.seeds, vec![1, 2]);
+        assert_eq!(almanac.convert("a", "d", 0), Ok(0));
+        assert_eq!(almanac.convert("a", "b", 3), Ok(13));
+        assert_eq!(almanac.path("a", "d").unwrap().len(), 2);
+        assert_eq!(
+            Almanac::from_str("1 2 3").err(),
+            Some(AlmanacError::InvalidLine("1 2 3".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parsing_map_entries() {
+        assert_eq!(parse_map_entry("50 98 2"), Some(((98, 100), 50)));
+        assert_eq!(parse_map_entry("10 x 5 7"), None);
+        assert_eq!(parse_map_entry("10 5"), None);
+        assert_eq!(parse_map_entry("10 5 7 8"), None);
+        assert_eq!(parse_map_entry("10 -5 7"), None);
+        assert_eq!(parse_map_entry(&format!("0 {} 2", u64::MAX - 1)), None);
+        assert_eq!(parse_map_entry(&format!("{} 0 2", u64::MAX - 1)), None);
+        assert_eq!(
+            parse_map_entry(&format!("0 {} 1", u64::MAX - 1)),
+            Some(((u64::MAX - 1, u64::MAX), 0))
+        );
+    }
+
+    #[test]
+    fn test_preimage_ranges() {
+        let function = PiecewiseLinear::from_map(&vec![((10, 20), 100), ((30, 35), 0)]);
+        assert_eq!(
+            function.preimage(&[(0, 3), (15, 25), (105, 107)]),
+            vec![(0, 3), (15, 17), (20, 25), (30, 33), (105, 107)]
+        );
+    }
+
+    #[test]
+    fn test_inverse_lookups() {
+        let almanac = load_test_almanac();
 
-        if unparsed_numbers.len() == 3 {
-            let (destination_range_start_str, source_range_start_str, range_len_str) = (
-                unparsed_numbers[0],
-                unparsed_numbers[1],
-                unparsed_numbers[2],
+        let preimages = almanac
+            .preimage_ranges("seed", "location", &[(46, 47)])
+            .unwrap();
+        assert_eq!(preimages[0], ("location".to_string(), vec![(46, 47)]));
+        assert_eq!(preimages.len(), 8);
+        assert_eq!(preimages[7], ("seed".to_string(), vec![(82, 83)]));
+
+        let chain: Vec<_> = almanac
+            .chain("seed", "location", 82)
+            .unwrap()
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect();
+        assert_eq!(chain, vec![82, 84, 84, 84, 77, 45, 46, 46]);
+    }
+
+    #[test]
+    fn test_lowest_location_searching_upwards() {
+        let almanac = load_test_almanac();
+
+        for seeds in [
+            vec![79, 14, 55, 13],
+            vec![0, 100],
+            vec![97, 3, 14, 1, 50, 1],
+        ] {
+            let ranges = seed_ranges(&seeds).unwrap();
+            assert_eq!(
+                almanac.lowest_reachable("seed", "location", &ranges),
+                Ok(Some(lowest_location_point_wise(&almanac, &seeds)))
             );
+        }
+        assert_eq!(almanac.lowest_reachable("seed", "location", &[]), Ok(None));
+    }
 
-            match destination_range_start_str.parse::<u64>() {
-                Ok(destinaton_range_start) => match source_range_start_str.parse::<u64>() {
-                    Ok(source_range_start) => match range_len_str.parse::<u64>() {
-                        Ok(range_len) => {
-                            println!(
-                                "Putting ({}, {}) => {} match",
-                                source_range_start,
-                                source_range_start + range_len,
-                                destinaton_range_start
-                            );
-                            current_map.insert(
-                                (source_range_start, source_range_start + range_len),
-                                destinaton_range_start,
-                            );
-                        }
-                        Err(_) => println!("Cannot parse {}", range_len_str),
-                    },
-                    Err(_) => println!("Cannot parse {}", source_range_start_str),
+    #[test]
+    fn test_validating_maps() {
+        let map = vec![((30, 40), 50), ((10, 20), 0), ((15, 25), 100)];
+        assert_eq!(
+            validate_map(&map),
+            vec![
+                MapIssue::Overlap {
+                    first: (10, 20),
+                    second: (15, 25)
                 },
-                Err(_) => println!("Cannot parse {}", destination_range_start_str),
-            }
-        }
+                MapIssue::Gap { start: 25, end: 30 },
+            ]
+        );
+        assert!(load_test_almanac().validate().is_empty());
+    }
+
+    #[test]
+    fn test_rejecting_overlapping_maps() {
+        let almanac = Almanac::from_str(
+            "seeds: 1\n\na-to-b map:\n100 10 10\n0 15 10\n\nb-to-c map:\n0 0 5\n9 4 2\n",
+        )
+        .unwrap();
+        let overlap = AlmanacError::OverlappingRanges {
+            stage: "a-to-b".to_string(),
+            first: (10, 20),
+            second: (15, 25),
+        };
+        assert_eq!(almanac.convert("a", "b", 12), Err(overlap));
+        assert!(almanac.compose("a", "c").is_err());
+
+        let overlaps: Vec<_> = almanac
+            .validate()
+            .into_iter()
+            .map(|(stage_name, issue)| format!("{}: {}", stage_name, issue))
+            .collect();
+        assert_eq!(
+            overlaps,
+            vec![
+                "a-to-b: overlapping source ranges [10, 20) and [15, 25)",
+                "b-to-c: overlapping source ranges [0, 5) and [4, 6)",
+            ]
+        );
     }
 
-    let mut lowest_location = u64::MAX;
-    for (seed_range_start, seed_range_end) in seeds {
-        for i in seed_range_start..seed_range_end {
-            let mut found = find_in_map(&seed_to_soil, i);
-            found = find_in_map(&soil_to_fertilizer, found);
-            found = find_in_map(&fertilizer_to_water, found);
-            found = find_in_map(&water_to_light, found);
-            found = find_in_map(&light_to_temperature, found);
-            found = find_in_map(&temperature_to_humidity, found);
-            found = find_in_map(&humidity_to_location, found);
-            if found < lowest_location {
-                lowest_location = found;
+    #[test]
+    fn test_composing_stages() {
+        let almanac = load_test_almanac();
+        let composed = almanac.compose("seed", "location").unwrap();
+
+        for (idx, segment) in composed.segments.iter().enumerate() {
+            assert!(segment.start < segment.end);
+            if idx > 0 {
+                assert_eq!(composed.segments[idx - 1].end, segment.start);
             }
         }
-        println!("Finished anylyzing range [{}, {})", seed_range_start, seed_range_end);
+        for seed in 0..200 {
+            assert_eq!(
+                composed.evaluate(seed),
+                almanac.convert("seed", "location", seed).unwrap()
+            );
+        }
+
+        let serialized = composed.serialize("seed", "location");
+        let reparsed = Almanac::from_str(&serialized).unwrap();
+        assert_eq!(reparsed.compose("seed", "location").unwrap(), composed);
     }
 
-    println!("Minimal location: {}", lowest_location);
+    #[test]
+    fn test_solving_d05_part1() {
+        let input_filepath = match std::env::current_dir() {
+            Ok(filepath) => filepath.join("input_d05_test"),
+            Err(_) => panic!("Cannot find current directory"),
+        };
+        assert_eq!(solve_part_01(&input_filepath), 35);
+    }
+
+    #[test]
+    fn test_solving_d05_part2() {
+        let input_filepath = match std::env::current_dir() {
+            Ok(filepath) => filepath.join("input_d05_test"),
+            Err(_) => panic!("Cannot find current directory"),
+        };
+        assert_eq!(solve_part_02(&input_filepath), 46);
+    }
 }
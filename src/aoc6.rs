@@ -1,111 +1,276 @@
-use std::fs;
-
-fn fill_vector_with_values(line: &str, vec: &mut Vec<u64>) {
-    let splitted_line: Vec<_> = line.split(":").collect();
-    if splitted_line.len() == 2 {
-        let times_str = splitted_line[1].split(" ");
-        for time_str in times_str {
-            match time_str.parse::<u64>() {
-                Ok(val) => vec.push(val),
-                Err(_) => (),
-            }
+use core::fmt;
+use std::{fs, path::Path};
+
+// Floor of the square root, exact for the whole u128 range
+fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    // Newton's method converges from above, so start with a power of two
+    // which is not lower than the root
+    let mut x = 1u128 << (128 - n.leading_zeros()).div_ceil(2);
+    loop {
+        let y = (x + n / x) / 2;
+        if y >= x {
+            return x;
         }
+        x = y;
     }
 }
 
-fn fill_vector_with_value(line: &str, vec: &mut Vec<u64>) {
-    let splitted_line: Vec<_> = line.split(":").collect();
-    if splitted_line.len() == 2 {
-        let time_str = splitted_line[1].replace(" ", "");
-        match time_str.parse::<u64>() {
-            Ok(val) => vec.push(val),
-            Err(_) => (),
-        }
+fn beats_record(hold_time: u128, race_time: u128, distance: u128) -> bool {
+    match hold_time.checked_mul(race_time - hold_time) {
+        Some(travelled) => travelled > distance,
+        // Travelled distance does not even fit in u128
+        None => true,
     }
 }
 
-pub fn day06_task01() {
-    let input_filepath = match std::env::current_dir() {
-        Ok(filepath) => filepath.join("input_d06_t01"),
-        Err(_) => panic!("Cannot find current directory"),
-    };
+// Number of hold times t in [0, time] for which t * (time - t) > distance.
+// Winning hold times are symmetric around time / 2, so it is enough to find
+// the lower root of t^2 - time * t + distance = 0.
+fn count_winning_hold_times(time: u128, distance: u128) -> u128 {
+    let half = time / 2;
+    if !beats_record(half, time, distance) {
+        return 0;
+    }
 
-    println!("Input filepath: {}", input_filepath.display());
+    let discriminant = time
+        .checked_mul(time)
+        .and_then(|time_squared| time_squared.checked_sub(distance.checked_mul(4)?));
+    let mut first_winning = match discriminant {
+        Some(discriminant) => (time - isqrt(discriminant)) / 2,
+        // Too big for the closed form, search for the boundary instead
+        None => {
+            let (mut lower, mut upper) = (0, half);
+            while lower < upper {
+                let middle = lower + (upper - lower) / 2;
+                if beats_record(middle, time, distance) {
+                    upper = middle;
+                } else {
+                    lower = middle + 1;
+                }
+            }
+            lower
+        }
+    };
 
-    let file_content = fs::read_to_string(input_filepath).expect("File could not be loaded");
+    // Integer square root is rounded down, so move to the exact boundary
+    while first_winning > 0 && beats_record(first_winning - 1, time, distance) {
+        first_winning -= 1;
+    }
+    while !beats_record(first_winning, time, distance) {
+        first_winning += 1;
+    }
 
-    let mut times: Vec<u64> = Vec::new();
-    let mut distances: Vec<u64> = Vec::new();
+    time - 2 * first_winning + 1
+}
 
-    for line in file_content.split("\n") {
-        if line.starts_with("Time") {
-            fill_vector_with_values(line, &mut times);
-            continue;
-        }
+// How the numbers on the sheet are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Interpretation {
+    // Every column is a separate race
+    ManyRaces,
+    // Spaces between digits are ignored, giving a single race
+    KernedRace,
+}
 
-        if line.starts_with("Distance") {
-            fill_vector_with_values(line, &mut distances);
-        }
-    }
+#[derive(Debug, PartialEq, Eq)]
+enum RaceSheetError {
+    MissingLine(&'static str),
+    InvalidNumber(String),
+    CountMismatch { times: usize, distances: usize },
+    MarginOverflow,
+}
 
-    let mut final_result = 1;
-    for (time, distance) in times.iter().zip(distances.iter()) {
-        let mut current = 0;
-        for t in 0..*time {
-            if (time - t) * t > *distance {
-                //println!("Holding for t={}: Passes", t);
-                current += 1;
+impl fmt::Display for RaceSheetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RaceSheetError::MissingLine(label) => write!(f, "Missing '{}' line", label),
+            RaceSheetError::InvalidNumber(number) => write!(f, "Cannot parse '{}'", number),
+            RaceSheetError::CountMismatch { times, distances } => {
+                write!(f, "Sheet has {} times, but {} distances", times, distances)
             }
+            RaceSheetError::MarginOverflow => write!(f, "Margin of error does not fit into u128"),
         }
-        println!(
-            "Time: {}, Distance: {}, Possibilities: {}",
-            time, distance, current
-        );
-        final_result *= current;
     }
+}
 
-    println!("Final result: {}", final_result);
+// Races as (time, record distance) pairs
+struct RaceSheet {
+    races: Vec<(u128, u128)>,
 }
 
-pub fn day06_task02() {
-    let input_filepath = match std::env::current_dir() {
-        Ok(filepath) => filepath.join("input_d06_t01"),
-        Err(_) => panic!("Cannot find current directory"),
+fn parse_sheet_line(
+    file_content: &str,
+    label: &'static str,
+    interpretation: Interpretation,
+) -> Result<Vec<u128>, RaceSheetError> {
+    let values = match file_content
+        .lines()
+        .find_map(|line| line.strip_prefix(label)?.strip_prefix(":"))
+    {
+        Some(values) => values,
+        None => return Err(RaceSheetError::MissingLine(label)),
     };
 
-    println!("Input filepath: {}", input_filepath.display());
+    let numbers: Vec<String> = match interpretation {
+        Interpretation::ManyRaces => values.split_whitespace().map(String::from).collect(),
+        Interpretation::KernedRace => vec![values.split_whitespace().collect()],
+    };
+    numbers
+        .iter()
+        .map(|number| {
+            number
+                .parse::<u128>()
+                .map_err(|_| RaceSheetError::InvalidNumber(number.to_string()))
+        })
+        .collect()
+}
 
-    let file_content = fs::read_to_string(input_filepath).expect("File could not be loaded");
+impl RaceSheet {
+    fn from_str(
+        file_content: &str,
+        interpretation: Interpretation,
+    ) -> Result<RaceSheet, RaceSheetError> {
+        let times = parse_sheet_line(file_content, "Time", interpretation)?;
+        let distances = parse_sheet_line(file_content, "Distance", interpretation)?;
+        if times.len() != distances.len() {
+            return Err(RaceSheetError::CountMismatch {
+                times: times.len(),
+                distances: distances.len(),
+            });
+        }
 
-    let mut times: Vec<u64> = Vec::new();
-    let mut distances: Vec<u64> = Vec::new();
+        Ok(RaceSheet {
+            races: times.into_iter().zip(distances).collect(),
+        })
+    }
 
-    for line in file_content.split("\n") {
-        if line.starts_with("Time") {
-            fill_vector_with_value(line, &mut times);
-            continue;
+    // Product of the numbers of ways to beat the record in every race
+    fn margin_of_error(&self) -> Result<u128, RaceSheetError> {
+        let mut final_result: u128 = 1;
+        for (time, distance) in &self.races {
+            let current = count_winning_hold_times(*time, *distance);
+            final_result = final_result
+                .checked_mul(current)
+                .ok_or(RaceSheetError::MarginOverflow)?;
         }
+        Ok(final_result)
+    }
+}
+
+fn solve(file_path: &Path, interpretation: Interpretation) -> u128 {
+    let file_content = fs::read_to_string(file_path).expect("File could not be loaded");
+    match RaceSheet::from_str(&file_content, interpretation)
+        .and_then(|sheet| sheet.margin_of_error())
+    {
+        Ok(margin_of_error) => margin_of_error,
+        Err(err) => panic!("{}", err),
+    }
+}
+
+pub fn solve_part_01(file_path: &Path) -> u128 {
+    solve(file_path, Interpretation::ManyRaces)
+}
 
-        if line.starts_with("Distance") {
-            fill_vector_with_value(line, &mut distances);
+pub fn solve_part_02(file_path: &Path) -> u128 {
+    solve(file_path, Interpretation::KernedRace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count_winning_hold_times_by_loop(time: u128, distance: u128) -> u128 {
+        (0..=time).filter(|t| (time - t) * t > distance).count() as u128
+    }
+
+    #[test]
+    fn test_isqrt() {
+        for n in 0..10_000u128 {
+            let root = isqrt(n);
+            assert!(root * root <= n && (root + 1) * (root + 1) > n);
         }
+        assert_eq!(isqrt(u128::MAX), u64::MAX as u128);
+        assert_eq!(isqrt((1 << 100) - 1), (1 << 50) - 1);
     }
 
-    let mut final_result = 1;
-    for (time, distance) in times.iter().zip(distances.iter()) {
-        let mut current = 0;
-        for t in 0..*time {
-            if (time - t) * t > *distance {
-                //println!("Holding for t={}: Passes", t);
-                current += 1;
+    #[test]
+    fn test_closed_form_matches_loop() {
+        for time in 0..80u128 {
+            for distance in 0..=time * time / 4 + 2 {
+                assert_eq!(
+                    count_winning_hold_times(time, distance),
+                    count_winning_hold_times_by_loop(time, distance),
+                    "time {} distance {}",
+                    time,
+                    distance
+                );
             }
         }
-        println!(
-            "Time: {}, Distance: {}, Possibilities: {}",
-            time, distance, current
+    }
+
+    #[test]
+    fn test_huge_races() {
+        // t * (time - t) > 0 holds for every t except both ends
+        assert_eq!(count_winning_hold_times(u128::MAX, 0), u128::MAX - 1);
+        let time = 1u128 << 64;
+        assert_eq!(count_winning_hold_times(time, (1 << 126) - 1), 1);
+        assert_eq!(count_winning_hold_times(time, 1 << 126), 0);
+        assert_eq!(count_winning_hold_times(time, (1 << 126) - 4), 3);
+    }
+
+    #[test]
+    fn test_parsing_race_sheet() {
+        let sheet = "Time:      7  15   30\nDistance:  9  40  200\n";
+        let many_races = RaceSheet::from_str(sheet, Interpretation::ManyRaces).unwrap();
+        assert_eq!(many_races.races, vec![(7, 9), (15, 40), (30, 200)]);
+        let kerned_race = RaceSheet::from_str(sheet, Interpretation::KernedRace).unwrap();
+        assert_eq!(kerned_race.races, vec![(71530, 940200)]);
+    }
+
+    #[test]
+    fn test_race_sheet_validation() {
+        assert_eq!(
+            RaceSheet::from_str("Time: 7 15\nDistance: 9\n", Interpretation::ManyRaces).err(),
+            Some(RaceSheetError::CountMismatch {
+                times: 2,
+                distances: 1
+            })
+        );
+        assert_eq!(
+            RaceSheet::from_str("Time: 7 15\n", Interpretation::KernedRace).err(),
+            Some(RaceSheetError::MissingLine("Distance"))
+        );
+        assert_eq!(
+            RaceSheet::from_str("Time: 7 x\nDistance: 9 1\n", Interpretation::ManyRaces).err(),
+            Some(RaceSheetError::InvalidNumber("x".to_string()))
+        );
+        let huge_races = RaceSheet {
+            races: vec![(1 << 70, 0), (1 << 70, 0)],
+        };
+        assert_eq!(
+            huge_races.margin_of_error(),
+            Err(RaceSheetError::MarginOverflow)
         );
-        final_result *= current;
     }
 
-    println!("Final result: {}", final_result);
+    #[test]
+    fn test_solving_d06_part1() {
+        let input_filepath = match std::env::current_dir() {
+            Ok(filepath) => filepath.join("input_d06_test"),
+            Err(_) => panic!("Cannot find current directory"),
+        };
+        assert_eq!(solve_part_01(&input_filepath), 288);
+    }
+
+    #[test]
+    fn test_solving_d06_part2() {
+        let input_filepath = match std::env::current_dir() {
+            Ok(filepath) => filepath.join("input_d06_test"),
+            Err(_) => panic!("Cannot find current directory"),
+        };
+        assert_eq!(solve_part_02(&input_filepath), 71503);
+    }
 }
@@ -1,256 +1,509 @@
 use core::fmt;
-use std::{collections::HashMap, fs};
-
-#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
-enum HandType {
-    FiveOfKind = 7,
-    FourOfKind = 6,
-    FullHouse = 5,
-    ThreeOfKind = 4,
-    TwoPairs = 3,
-    OnePair = 2,
-    HighCard = 1,
+use std::{cmp::Ordering, collections::HashMap, fs, path::Path};
+
+// Sizes of groups of equal cards, from the biggest one. Comparing them
+// lexicographically gives the usual order of categories for any hand size,
+// e.g. [5] > [4, 1] > [3, 2] > [3, 1, 1] > [2, 2, 1] > [2, 1, 1, 1] > [1, 1, 1, 1, 1]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct HandType {
+    counts: Vec<usize>,
+}
+
+fn count_name(count: usize) -> String {
+    match count {
+        2 => "Pair".to_string(),
+        3 => "ThreeOfKind".to_string(),
+        4 => "FourOfKind".to_string(),
+        5 => "FiveOfKind".to_string(),
+        6 => "SixOfKind".to_string(),
+        7 => "SevenOfKind".to_string(),
+        8 => "EightOfKind".to_string(),
+        9 => "NineOfKind".to_string(),
+        _ => format!("{}OfKind", count),
+    }
 }
 
 impl fmt::Display for HandType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:?}", self)
+        let groups: Vec<_> = self.counts.iter().filter(|count| **count > 1).collect();
+        match groups.as_slice() {
+            [] => return write!(f, "HighCard"),
+            [2] => return write!(f, "OnePair"),
+            [2, 2] => return write!(f, "TwoPairs"),
+            [3, 2] => return write!(f, "FullHouse"),
+            _ => (),
+        }
+
+        let mut names = Vec::new();
+        for group in groups.chunk_by(|a, b| a == b) {
+            let prefix = match group.len() {
+                1 => String::new(),
+                2 => "Double".to_string(),
+                3 => "Triple".to_string(),
+                times => format!("{}x", times),
+            };
+            names.push(prefix + &count_name(*group[0]));
+        }
+        write!(f, "{}", names.join("And"))
     }
 }
 
-fn parse_card_label(label: char) -> u8 {
-    match label {
-        '3' => 2,
-        '4' => 3,
-        '5' => 4,
-        '6' => 5,
-        '7' => 6,
-        '8' => 7,
-        '9' => 8,
-        'T' => 9,
-        'J' => 10,
-        'Q' => 11,
-        'K' => 12,
-        'A' => 13,
-        _ => 1,
-    }
+// How hands with the same type are ordered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TieBreak {
+    // Cards are compared one by one in the order they were dealt
+    InOrder,
+    // Cards are compared from the strongest to the weakest one
+    Sorted,
 }
 
-fn parse_card(line: &str) -> Option<(HandType, (u8, u8, u8, u8, u8), u64)> {
-    let mut values: HashMap<char, u8> = HashMap::new();
-    let splitted_line: Vec<_> = line.split(" ").collect();
-    if splitted_line.len() == 2 {
-        let (hand, rank) = (splitted_line[0], splitted_line[1]);
-        let rank_val = match rank.parse::<u64>() {
-            Ok(val) => val,
-            Err(_) => 0,
-        };
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rules {
+    // All valid card labels from the weakest to the strongest
+    card_order: &'static str,
+    // Card which acts like whatever card makes the hand strongest
+    wild_card: Option<char>,
+    tie_break: TieBreak,
+    // Number of cards in every hand, any number when not set
+    hand_size: Option<usize>,
+}
 
-        for c in hand.chars() {
-            let current_val = match values.get(&c) {
-                Some(v) => *v,
-                None => 0,
-            };
-            values.insert(c, current_val + 1);
-        }
+const STANDARD_RULES: Rules = Rules {
+    card_order: "23456789TJQKA",
+    wild_card: None,
+    tie_break: TieBreak::InOrder,
+    hand_size: Some(5),
+};
 
-        let is_four_in_hand = values.values().filter(|v| **v == 4).count() == 1;
-        let is_three_in_hand = values.values().filter(|v| **v == 3).count() == 1;
-        let number_of_twos = values.values().filter(|v| **v == 2).count();
-        let hand_type: HandType;
-        if values.len() == 1 {
-            hand_type = HandType::FiveOfKind;
-        } else if values.len() == 5 {
-            hand_type = HandType::HighCard;
-        } else if is_four_in_hand {
-            hand_type = HandType::FourOfKind;
-        } else if is_three_in_hand {
-            hand_type = match number_of_twos {
-                0 => HandType::ThreeOfKind,
-                _ => HandType::FullHouse,
-            };
-        } else {
-            hand_type = match number_of_twos {
-                2 => HandType::TwoPairs,
-                _ => HandType::OnePair,
-            };
-        }
+const JOKER_RULES: Rules = Rules {
+    card_order: "J23456789TQKA",
+    wild_card: Some('J'),
+    tie_break: TieBreak::InOrder,
+    hand_size: Some(5),
+};
 
-        let parsed_values: Vec<_> = hand.chars().map(|v| parse_card_label(v)).collect();
+#[derive(Debug, PartialEq, Eq)]
+enum HandError {
+    MalformedLine(String),
+    UnknownCard(char),
+    WrongHandSize { expected: usize, found: usize },
+    InvalidBid(String),
+}
 
-        return Some((
-            hand_type,
-            (
-                parsed_values[0],
-                parsed_values[1],
-                parsed_values[2],
-                parsed_values[3],
-                parsed_values[4],
-            ),
-            rank_val,
-        ));
+impl fmt::Display for HandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HandError::MalformedLine(line) => write!(f, "Cannot parse hand '{}'", line),
+            HandError::UnknownCard(card) => write!(f, "Invalid card label '{}'", card),
+            HandError::WrongHandSize { expected, found } => {
+                write!(f, "Hand has {} cards instead of {}", found, expected)
+            }
+            HandError::InvalidBid(bid) => write!(f, "Invalid bid '{}'", bid),
+        }
     }
-    None
 }
 
-fn parse_card_label_with_joker_lowest(label: char) -> u8 {
-    match label {
-        '3' => 2,
-        '4' => 3,
-        '5' => 4,
-        '6' => 5,
-        '7' => 6,
-        '8' => 7,
-        '9' => 8,
-        'T' => 9,
-        'J' => 0,
-        'Q' => 11,
-        'K' => 12,
-        'A' => 13,
-        _ => 1,
-    }
+#[derive(Debug)]
+struct Hand {
+    cards: String,
+    hand_type: HandType,
+    // Card strengths in the order they are compared on tie
+    tie_break_values: Vec<usize>,
+    bid: u64,
+    // Card which the wild cards in this hand pretend to be
+    wild_substitute: Option<char>,
 }
 
-fn parse_card_with_jokers(line: &str) -> Option<(HandType, (u8, u8, u8, u8, u8), u64)> {
-    let mut values: HashMap<char, u8> = HashMap::new();
-    let splitted_line: Vec<_> = line.split(" ").collect();
-    if splitted_line.len() == 2 {
-        let (hand, rank) = (splitted_line[0], splitted_line[1]);
-        let rank_val = match rank.parse::<u64>() {
-            Ok(val) => val,
-            Err(_) => 0,
+impl Hand {
+    fn parse(line: &str, rules: &Rules) -> Result<Hand, HandError> {
+        let (cards, bid) = match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [cards, bid] => (cards.to_string(), *bid),
+            _ => return Err(HandError::MalformedLine(line.to_string())),
         };
+        let bid = bid
+            .parse::<u64>()
+            .map_err(|_| HandError::InvalidBid(bid.to_string()))?;
 
-        for c in hand.chars() {
-            let current_val = match values.get(&c) {
-                Some(v) => *v,
-                None => 0,
-            };
-            values.insert(c, current_val + 1);
+        let mut tie_break_values = Vec::new();
+        for card in cards.chars() {
+            match rules.card_order.find(card) {
+                Some(strength) => tie_break_values.push(strength),
+                None => return Err(HandError::UnknownCard(card)),
+            }
+        }
+        match rules.hand_size {
+            Some(expected) if expected != tie_break_values.len() => {
+                return Err(HandError::WrongHandSize {
+                    expected,
+                    found: tie_break_values.len(),
+                });
+            }
+            _ => (),
+        }
+        if rules.tie_break == TieBreak::Sorted {
+            tie_break_values.sort_by(|a, b| b.cmp(a));
+        }
+
+        let (hand_type, wild_substitute) = Hand::hand_type(&cards, rules);
+        Ok(Hand {
+            cards,
+            hand_type,
+            tie_break_values,
+            bid,
+            wild_substitute,
+        })
+    }
+
+    fn hand_type(cards: &str, rules: &Rules) -> (HandType, Option<char>) {
+        let mut values: HashMap<char, usize> = HashMap::new();
+        for c in cards.chars() {
+            *values.entry(c).or_insert(0) += 1;
         }
 
-        let number_of_jokers = match values.remove(&'J') {
-            Some(v) => v,
+        let number_of_wild_cards = match rules.wild_card {
+            Some(wild_card) => values.remove(&wild_card).unwrap_or(0),
             None => 0,
         };
 
-        let (mut card_with_maximum_values, mut amount) = ('X', 0);
-        for (c, v) in &values {
-            if *v > amount {
-                card_with_maximum_values = *c;
-                amount = *v;
-            }
-        }
-     
-        values.insert(card_with_maximum_values, amount+number_of_jokers);
-        println!("Card with maximum values {} ({})", card_with_maximum_values, amount);
-
-        let is_four_in_hand = values.values().filter(|v| **v == 4).count() == 1;
-        let is_three_in_hand = values.values().filter(|v| **v == 3).count() == 1;
-        let number_of_twos = values.values().filter(|v| **v == 2).count();
-        let hand_type: HandType;
-        if values.len() == 1 {
-            hand_type = HandType::FiveOfKind;
-        } else if values.len() == 5 {
-            hand_type = HandType::HighCard;
-        } else if is_four_in_hand {
-            hand_type = HandType::FourOfKind;
-        } else if is_three_in_hand {
-            hand_type = match number_of_twos {
-                0 => HandType::ThreeOfKind,
-                _ => HandType::FullHouse,
-            };
-        } else {
-            hand_type = match number_of_twos {
-                2 => HandType::TwoPairs,
-                _ => HandType::OnePair,
-            };
+        // Wild cards become the strongest of the most numerous cards,
+        // or simply the strongest card when there is nothing else in hand
+        let wild_substitute = match number_of_wild_cards {
+            0 => None,
+            _ => values
+                .iter()
+                .max_by_key(|(card, count)| (**count, rules.card_order.find(**card)))
+                .map(|(card, _)| *card)
+                .or_else(|| {
+                    rules
+                        .card_order
+                        .chars()
+                        .rev()
+                        .find(|card| Some(*card) != rules.wild_card)
+                }),
+        };
+
+        let mut counts: Vec<_> = values.into_values().collect();
+        counts.sort_by(|a, b| b.cmp(a));
+        // Wild cards always join the most numerous group
+        match counts.first_mut() {
+            Some(count) => *count += number_of_wild_cards,
+            None => counts.push(number_of_wild_cards),
         }
+        counts.retain(|count| *count > 0);
 
-        let parsed_values: Vec<_> = hand
-            .chars()
-            .map(|v| parse_card_label_with_joker_lowest(v))
-            .collect();
+        (HandType { counts }, wild_substitute)
+    }
+}
 
-        return Some((
-            hand_type,
-            (
-                parsed_values[0],
-                parsed_values[1],
-                parsed_values[2],
-                parsed_values[3],
-                parsed_values[4],
-            ),
-            rank_val,
-        ));
+impl Ord for Hand {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.hand_type
+            .cmp(&other.hand_type)
+            .then_with(|| self.tie_break_values.cmp(&other.tie_break_values))
+    }
+}
+
+// Hands are equal when they are equally strong, bids do not matter
+impl PartialEq for Hand {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
     }
-    None
 }
 
-pub fn day07_task01() {
-    let input_filepath = match std::env::current_dir() {
-        Ok(filepath) => filepath.join("input_d07_t01"),
-        //Ok(filepath) => filepath.join("input_d07_test"),
-        Err(_) => panic!("Cannot find current directory"),
-    };
+impl Eq for Hand {}
 
-    println!("Input filepath: {}", input_filepath.display());
+impl PartialOrd for Hand {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
-    let file_content = fs::read_to_string(input_filepath).expect("File could not be loaded");
+fn parse_hands(file_content: &str, rules: &Rules) -> Result<Vec<Hand>, HandError> {
+    let mut hands = Vec::new();
+    for line in file_content.lines().filter(|line| !line.trim().is_empty()) {
+        hands.push(Hand::parse(line, rules)?);
+    }
+    Ok(hands)
+}
 
-    let mut all_cards: Vec<_> = Vec::new();
+// Rank of every hand, in the order hands were given
+fn rank_hands(hands: &[Hand]) -> Vec<u64> {
+    let mut order: Vec<_> = (0..hands.len()).collect();
+    order.sort_by(|a, b| hands[*a].cmp(&hands[*b]));
 
-    for line in file_content.split("\n") {
-        println!("{}", line);
-        if let Some((hand_type, values, rank_val)) = parse_card(line) {
-            println!(
-                "HandType: {}, rank: {}, values: {}",
-                hand_type, rank_val, values.0
-            );
-            all_cards.push((hand_type, values, rank_val));
-        }
+    let mut ranks = vec![0; hands.len()];
+    for (i, hand_idx) in order.iter().enumerate() {
+        ranks[*hand_idx] = i as u64 + 1;
     }
+    ranks
+}
+
+fn total_winnings(file_content: &str, rules: &Rules) -> Result<u64, HandError> {
+    let hands = parse_hands(file_content, rules)?;
+    let ranks = rank_hands(&hands);
+
+    Ok(hands
+        .iter()
+        .zip(ranks)
+        .map(|(hand, rank)| rank * hand.bid)
+        .sum())
+}
+
+fn solve(file_path: &Path, rules: &Rules) -> u64 {
+    let file_content = fs::read_to_string(file_path).expect("File could not be loaded");
+    match total_winnings(&file_content, rules) {
+        Ok(total_winnings) => total_winnings,
+        Err(err) => panic!("{}", err),
+    }
+}
 
-    all_cards.sort();
+pub fn solve_part_01(file_path: &Path) -> u64 {
+    solve(file_path, &STANDARD_RULES)
+}
+
+pub fn solve_part_02(file_path: &Path) -> u64 {
+    solve(file_path, &JOKER_RULES)
+}
+
+fn rules_by_name(name: &str) -> Option<Rules> {
+    match name {
+        "standard" => Some(STANDARD_RULES),
+        "joker" => Some(JOKER_RULES),
+        _ => None,
+    }
+}
+
+fn substitution(hand: &Hand, rules: &Rules) -> String {
+    match (rules.wild_card, hand.wild_substitute) {
+        (Some(wild_card), Some(substitute)) => format!("{}->{}", wild_card, substitute),
+        _ => "-".to_string(),
+    }
+}
+
+// Hands in final rank order with everything that decided about their rank
+fn ranking_report(file_content: &str, rules: &Rules) -> Result<String, HandError> {
+    let hands = parse_hands(file_content, rules)?;
+    let ranks = rank_hands(&hands);
+    let mut ranked: Vec<_> = hands.iter().zip(ranks).collect();
+    ranked.sort_by_key(|(_, rank)| *rank);
+
+    let mut report = format!(
+        "{:>5} {:>8} {:>18} {:>8} {:>6} {:>10}\n",
+        "rank", "hand", "category", "wild", "bid", "winnings"
+    );
     let mut total_winnings = 0;
-    for (i, (hand_type, values, rank_val)) in all_cards.iter().enumerate() {
-        let val: u64 = i as u64 + 1;
-        println!("{} {} {}", val, hand_type, values.0);
-        total_winnings += val * rank_val;
+    for (hand, rank) in ranked {
+        report += &format!(
+            "{:>5} {:>8} {:>18} {:>8} {:>6} {:>10}\n",
+            rank,
+            hand.cards,
+            hand.hand_type.to_string(),
+            substitution(hand, rules),
+            hand.bid,
+            rank * hand.bid
+        );
+        total_winnings += rank * hand.bid;
     }
-    println!("Total winnings {}", total_winnings);
+    report += &format!("Total winnings {}\n", total_winnings);
+    Ok(report)
 }
 
-pub fn day07_task02() {
-    let input_filepath = match std::env::current_dir() {
-        Ok(filepath) => filepath.join("input_d07_t01"),
-        //Ok(filepath) => filepath.join("input_d07_test"),
-        Err(_) => panic!("Cannot find current directory"),
-    };
+// Hands which changed their rank between two rule sets
+fn ranking_diff(file_content: &str, first: &Rules, second: &Rules) -> Result<String, HandError> {
+    let first_hands = parse_hands(file_content, first)?;
+    let second_hands = parse_hands(file_content, second)?;
+    let first_ranks = rank_hands(&first_hands);
+    let second_ranks = rank_hands(&second_hands);
 
-    println!("Input filepath: {}", input_filepath.display());
+    let mut changed: Vec<_> = (0..first_hands.len())
+        .filter(|idx| first_ranks[*idx] != second_ranks[*idx])
+        .collect();
+    changed.sort_by_key(|idx| second_ranks[*idx]);
 
-    let file_content = fs::read_to_string(input_filepath).expect("File could not be loaded");
+    let mut diff = format!(
+        "{:>8} {:>5} {:>5} {:>6} {:>18} {:>18} {:>10}\n",
+        "hand", "from", "to", "change", "category before", "category after", "winnings"
+    );
+    for idx in &changed {
+        let (first_hand, second_hand) = (&first_hands[*idx], &second_hands[*idx]);
+        let rank_change = second_ranks[*idx] as i64 - first_ranks[*idx] as i64;
+        diff += &format!(
+            "{:>8} {:>5} {:>5} {:>+6} {:>18} {:>18} {:>+10}\n",
+            first_hand.cards,
+            first_ranks[*idx],
+            second_ranks[*idx],
+            rank_change,
+            first_hand.hand_type.to_string(),
+            second_hand.hand_type.to_string(),
+            rank_change * first_hand.bid as i64
+        );
+    }
+    diff += &format!(
+        "{} of {} hands changed their rank\n",
+        changed.len(),
+        first_hands.len()
+    );
+    Ok(diff)
+}
 
-    let mut all_cards: Vec<_> = Vec::new();
+pub fn print_report(file_path: &Path, rules_name: &str) {
+    let file_content = fs::read_to_string(file_path).expect("File could not be loaded");
+    let rules = rules_by_name(rules_name).expect("Unknown rules");
+    match ranking_report(&file_content, &rules) {
+        Ok(report) => print!("{}", report),
+        Err(err) => println!("{}", err),
+    }
+}
 
-    for line in file_content.split("\n") {
-        println!("{}", line);
-        if let Some((hand_type, values, rank_val)) = parse_card_with_jokers(line) {
-            println!(
-                "HandType: {}, rank: {}, values: {}",
-                hand_type, rank_val, values.0
-            );
-            all_cards.push((hand_type, values, rank_val));
-        }
+pub fn print_diff(file_path: &Path, first_rules_name: &str, second_rules_name: &str) {
+    let file_content = fs::read_to_string(file_path).expect("File could not be loaded");
+    let first = rules_by_name(first_rules_name).expect("Unknown rules");
+    let second = rules_by_name(second_rules_name).expect("Unknown rules");
+    match ranking_diff(&file_content, &first, &second) {
+        Ok(diff) => print!("{}", diff),
+        Err(err) => println!("{}", err),
     }
+}
 
-    all_cards.sort();
-    let mut total_winnings = 0;
-    for (i, (hand_type, values, rank_val)) in all_cards.iter().enumerate() {
-        let val: u64 = i as u64 + 1;
-        println!("{} {} {}", val, hand_type, values.0);
-        total_winnings += val * rank_val;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hand_types() {
+        let hand_type =
+            |line: &str, rules: &Rules| Hand::parse(line, rules).unwrap().hand_type.to_string();
+        assert_eq!(hand_type("KTJJT 1", &STANDARD_RULES), "TwoPairs");
+        assert_eq!(hand_type("KTJJT 1", &JOKER_RULES), "FourOfKind");
+        assert_eq!(hand_type("JJJJJ 1", &JOKER_RULES), "FiveOfKind");
+        assert_eq!(hand_type("23456 1", &STANDARD_RULES), "HighCard");
+        assert_eq!(hand_type("2345J 1", &JOKER_RULES), "OnePair");
+        assert_eq!(hand_type("2233J 1", &JOKER_RULES), "FullHouse");
+        assert_eq!(hand_type("22A22 1", &STANDARD_RULES), "FourOfKind");
+    }
+
+    #[test]
+    fn test_variable_hand_sizes() {
+        let rules = Rules {
+            hand_size: None,
+            ..JOKER_RULES
+        };
+        let hand = |line: &str| Hand::parse(line, &rules).unwrap();
+        assert_eq!(hand("222J22 1").hand_type.to_string(), "SixOfKind");
+        assert_eq!(hand("333AAA 1").hand_type.to_string(), "DoubleThreeOfKind");
+        assert_eq!(hand("3333AA 1").hand_type.to_string(), "FourOfKindAndPair");
+        assert_eq!(hand("22334455 1").hand_type.to_string(), "4xPair");
+        assert_eq!(hand("AK 1").hand_type.to_string(), "HighCard");
+
+        assert!(hand("222222 1") > hand("22222A 1"));
+        assert!(hand("2222AA 1") > hand("2222AK 1"));
+        assert!(hand("2222AK 1") > hand("222AAA 1"));
+        assert!(hand("222AAA 1") > hand("222AAK 1"));
+        assert!(hand("222AAA 1") == hand("222AAA 7"));
+    }
+
+    #[test]
+    fn test_ordering_hands() {
+        let hand = |line: &str, rules: &Rules| Hand::parse(line, rules).unwrap();
+        // '2' used to have the same strength as invalid labels
+        assert!(hand("22345 1", &STANDARD_RULES) < hand("33245 1", &STANDARD_RULES));
+        assert!(hand("J2345 1", &STANDARD_RULES) > hand("T2345 1", &STANDARD_RULES));
+        assert!(hand("J2345 1", &JOKER_RULES) < hand("22345 1", &JOKER_RULES));
+
+        let sorted_rules = Rules {
+            tie_break: TieBreak::Sorted,
+            ..STANDARD_RULES
+        };
+        assert!(hand("2345A 1", &STANDARD_RULES) < hand("K2345 1", &STANDARD_RULES));
+        assert!(hand("2345A 1", &sorted_rules) > hand("K2345 1", &sorted_rules));
+    }
+
+    #[test]
+    fn test_rejecting_invalid_hands() {
+        assert_eq!(
+            Hand::parse("2345X 1", &STANDARD_RULES),
+            Err(HandError::UnknownCard('X'))
+        );
+        assert_eq!(
+            Hand::parse("2345 1", &STANDARD_RULES),
+            Err(HandError::WrongHandSize {
+                expected: 5,
+                found: 4
+            })
+        );
+        assert_eq!(
+            Hand::parse("23456 x", &STANDARD_RULES),
+            Err(HandError::InvalidBid("x".to_string()))
+        );
+        assert_eq!(
+            Hand::parse("23456", &STANDARD_RULES),
+            Err(HandError::MalformedLine("23456".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_wild_substitutes() {
+        let substitute = |line: &str| Hand::parse(line, &JOKER_RULES).unwrap().wild_substitute;
+        assert_eq!(substitute("KTJJT 1"), Some('T'));
+        assert_eq!(substitute("KKJTT 1"), Some('K'));
+        assert_eq!(substitute("JJJJJ 1"), Some('A'));
+        assert_eq!(substitute("KKTTQ 1"), None);
+    }
+
+    #[test]
+    fn test_ranking_report() {
+        let report = ranking_report("32T3K 765\nKTJJT 220\nKK677 28\n", &JOKER_RULES).unwrap();
+        let lines: Vec<_> = report
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>())
+            .collect();
+        assert_eq!(lines[1], vec!["1", "32T3K", "OnePair", "-", "765", "765"]);
+        assert_eq!(lines[2], vec!["2", "KK677", "TwoPairs", "-", "28", "56"]);
+        assert_eq!(
+            lines[3],
+            vec!["3", "KTJJT", "FourOfKind", "J->T", "220", "660"]
+        );
+        assert_eq!(report.lines().last(), Some("Total winnings 1481"));
+    }
+
+    #[test]
+    fn test_ranking_diff() {
+        let diff = ranking_diff(
+            "32T3K 765\nKTJJT 220\nKK677 28\n",
+            &STANDARD_RULES,
+            &JOKER_RULES,
+        )
+        .unwrap();
+        let lines: Vec<_> = diff
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>())
+            .collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[1],
+            vec!["KK677", "3", "2", "-1", "TwoPairs", "TwoPairs", "-28"]
+        );
+        assert_eq!(
+            lines[2],
+            vec!["KTJJT", "2", "3", "+1", "TwoPairs", "FourOfKind", "+220"]
+        );
+    }
+
+    #[test]
+    fn test_solving_d07_part1() {
+        let input_filepath = match std::env::current_dir() {
+            Ok(filepath) => filepath.join("input_d07_test"),
+            Err(_) => panic!("Cannot find current directory"),
+        };
+        assert_eq!(solve_part_01(&input_filepath), 6440);
+    }
+
+    #[test]
+    fn test_solving_d07_part2() {
+        let input_filepath = match std::env::current_dir() {
+            Ok(filepath) => filepath.join("input_d07_test"),
+            Err(_) => panic!("Cannot find current directory"),
+        };
+        assert_eq!(solve_part_02(&input_filepath), 5905);
     }
-    println!("Total winnings {}", total_winnings);
 }
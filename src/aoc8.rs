@@ -1,198 +1,874 @@
-use std::{collections::HashMap, fs};
+use core::fmt;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
 
+use crate::math::{self, CrtError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Direction {
     Right,
     Left,
 }
 
-fn lcm(first: usize, second: usize) -> usize {
-    first * second / gcd(first, second)
+#[derive(Debug, PartialEq, Eq)]
+enum NetworkError {
+    MissingInstructions,
+    InvalidInstruction(char),
+    InvalidLine(String),
+    DuplicatedNode(String),
 }
 
-fn gcd(first: usize, second: usize) -> usize {
-    let mut max = first;
-    let mut min = second;
-    if min > max {
-        let val = max;
-        max = min;
-        min = val;
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkError::MissingInstructions => write!(f, "Missing instructions line"),
+            NetworkError::InvalidInstruction(c) => write!(f, "Unknown instruction '{}'", c),
+            NetworkError::InvalidLine(line) => write!(f, "Cannot parse '{}'", line),
+            NetworkError::DuplicatedNode(name) => write!(f, "Node {} is defined twice", name),
+        }
     }
+}
 
-    loop {
-        let res = max % min;
-        if res == 0 {
-            return min;
-        }
+// Nodes are interned into indices, so walking the network only moves
+// between indices and never touches the names
+struct Network {
+    instructions: Vec<Direction>,
+    names: Vec<String>,
+    // (left, right) per node, nodes which are referenced but never defined
+    // lead back to themselves
+    nodes: Vec<(usize, usize)>,
+    defined: Vec<bool>,
+    indices: HashMap<String, usize>,
+}
+
+fn parse_instructions(line: &str) -> Result<Vec<Direction>, NetworkError> {
+    line.trim()
+        .chars()
+        .map(|c| match c {
+            'R' => Ok(Direction::Right),
+            'L' => Ok(Direction::Left),
+            _ => Err(NetworkError::InvalidInstruction(c)),
+        })
+        .collect()
+}
+
+fn parse_crossroad(line: &str) -> Option<(&str, &str, &str)> {
+    let (name, targets) = line.split_once(" = ")?;
+    let (left, right) = targets
+        .strip_prefix("(")?
+        .strip_suffix(")")?
+        .split_once(", ")?;
+    Some((name.trim(), left.trim(), right.trim()))
+}
+
+// Selects nodes by name
+#[derive(Debug, PartialEq, Eq)]
+enum NodePattern {
+    Exact(String),
+    Suffix(String),
+    // '*' matches any number of characters, '?' exactly one
+    Glob(String),
+}
 
-        max = min;
-        min = res;
+// Whole name has to match, backtracks only to the last '*'
+fn glob_matches(pattern: &[char], name: &[char]) -> bool {
+    let (mut pattern_idx, mut name_idx) = (0, 0);
+    let mut last_star: Option<(usize, usize)> = None;
+    while name_idx < name.len() {
+        match pattern.get(pattern_idx) {
+            Some('*') => {
+                last_star = Some((pattern_idx, name_idx));
+                pattern_idx += 1;
+            }
+            Some(c) if *c == '?' || *c == name[name_idx] => {
+                pattern_idx += 1;
+                name_idx += 1;
+            }
+            _ => match last_star {
+                Some((star_idx, star_name_idx)) => {
+                    pattern_idx = star_idx + 1;
+                    name_idx = star_name_idx + 1;
+                    last_star = Some((star_idx, star_name_idx + 1));
+                }
+                None => return false,
+            },
+        }
     }
+    pattern[pattern_idx..].iter().all(|c| *c == '*')
 }
 
-fn parse_instructions(line: &str) -> Vec<Direction> {
-    let mut directions = Vec::new();
-    for c in line.chars() {
-        match c {
-            'R' => directions.push(Direction::Right),
-            'L' => directions.push(Direction::Left),
-            _ => (),
+impl NodePattern {
+    // "exact:AAA", "suffix:Z" or "glob:?*Z", plain names are exact
+    fn parse(pattern: &str) -> Option<NodePattern> {
+        match pattern.split_once(":") {
+            Some(("exact", name)) => Some(NodePattern::Exact(name.to_string())),
+            Some(("suffix", suffix)) => Some(NodePattern::Suffix(suffix.to_string())),
+            Some(("glob", glob)) => Some(NodePattern::Glob(glob.to_string())),
+            Some(_) => None,
+            None => Some(NodePattern::Exact(pattern.to_string())),
         }
     }
 
-    directions
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            NodePattern::Exact(exact) => name == exact,
+            NodePattern::Suffix(suffix) => name.ends_with(suffix.as_str()),
+            NodePattern::Glob(glob) => {
+                let glob: Vec<_> = glob.chars().collect();
+                let name: Vec<_> = name.chars().collect();
+                glob_matches(&glob, &name)
+            }
+        }
+    }
 }
 
-fn add_crossroad_to_map(map: &mut HashMap<String, (String, String)>, line: &str) {
-    let splitted_line: Vec<_> = line.split(" = ").collect();
-    if splitted_line.len() == 2 {
-        let key = splitted_line[0].to_string();
+impl Network {
+    fn intern(&mut self, name: &str) -> usize {
+        if let Some(idx) = self.indices.get(name) {
+            return *idx;
+        }
+        let idx = self.names.len();
+        self.names.push(name.to_string());
+        self.nodes.push((idx, idx));
+        self.defined.push(false);
+        self.indices.insert(name.to_string(), idx);
+        idx
+    }
+
+    fn from_str(file_content: &str) -> Result<Network, NetworkError> {
+        let mut lines = file_content.lines().filter(|line| !line.trim().is_empty());
+        let instructions = parse_instructions(lines.next().unwrap_or(""))?;
+        if instructions.is_empty() {
+            return Err(NetworkError::MissingInstructions);
+        }
+
+        let mut network = Network {
+            instructions,
+            names: Vec::new(),
+            nodes: Vec::new(),
+            defined: Vec::new(),
+            indices: HashMap::new(),
+        };
+        for line in lines {
+            let (name, left, right) =
+                parse_crossroad(line).ok_or_else(|| NetworkError::InvalidLine(line.to_string()))?;
+            let idx = network.intern(name);
+            if network.defined[idx] {
+                return Err(NetworkError::DuplicatedNode(name.to_string()));
+            }
+            let targets = (network.intern(left), network.intern(right));
+            network.nodes[idx] = targets;
+            network.defined[idx] = true;
+        }
+        Ok(network)
+    }
 
-        let splitted_values: Vec<_> = splitted_line[1].split(", ").collect();
+    fn step(&self, node: usize, direction: Direction) -> usize {
+        match direction {
+            Direction::Left => self.nodes[node].0,
+            Direction::Right => self.nodes[node].1,
+        }
+    }
 
-        if splitted_values.len() == 2 {
-            let (left, right) = (
-                splitted_values[0].strip_prefix("(").unwrap().to_string(),
-                splitted_values[1].strip_suffix(")").unwrap().to_string(),
-            );
+    fn node(&self, name: &str) -> Option<usize> {
+        self.indices.get(name).copied()
+    }
 
-            map.insert(key, (left, right));
+    // Nodes whose name matches the pattern, ordered by name
+    fn matching(&self, pattern: &NodePattern) -> Vec<usize> {
+        let mut nodes: Vec<_> = (0..self.names.len())
+            .filter(|idx| pattern.matches(&self.names[*idx]))
+            .collect();
+        nodes.sort_by(|a, b| self.names[*a].cmp(&self.names[*b]));
+        nodes
+    }
+
+    // Number of steps until a goal is reached, None when the walk starts
+    // repeating itself without reaching any goal
+    fn steps_until(
+        &self,
+        start: usize,
+        is_goal: &[bool],
+        instructions: &[Direction],
+    ) -> Option<usize> {
+        let states_count = self.nodes.len() * instructions.len();
+        let mut node = start;
+        for (steps, direction) in instructions.iter().cycle().enumerate() {
+            if steps > states_count {
+                return None;
+            }
+            node = self.step(node, *direction);
+            if is_goal[node] {
+                return Some(steps + 1);
+            }
         }
+        None
     }
-}
 
-pub fn day08_task01() {
-    let input_filepath = match std::env::current_dir() {
-        Ok(filepath) => filepath.join("input_d08_t01"),
-        //Ok(filepath) => filepath.join("input_d08_test2"),
-        //Ok(filepath) => filepath.join("input_d08_test"),
-        Err(_) => panic!("Cannot find current directory"),
-    };
+    // Nodes visited on the walk measured by steps_until, including the start
+    fn walk_path(
+        &self,
+        start: usize,
+        is_goal: &[bool],
+        instructions: &[Direction],
+    ) -> Option<Vec<usize>> {
+        let steps = self.steps_until(start, is_goal, instructions)?;
+        let mut path = vec![start];
+        for direction in instructions.iter().cycle().take(steps) {
+            path.push(self.step(path[path.len() - 1], *direction));
+        }
+        Some(path)
+    }
+
+    fn goals(&self, nodes: &[usize]) -> Vec<bool> {
+        let mut is_goal = vec![false; self.nodes.len()];
+        for node in nodes {
+            is_goal[*node] = true;
+        }
+        is_goal
+    }
+}
 
-    println!("Input filepath: {}", input_filepath.display());
+// Walk of a single ghost, which ends up cycling through (node, instruction
+// index) states after its pre-period
+#[derive(Debug, PartialEq, Eq)]
+struct GhostCycle {
+    pre_period: usize,
+    period: usize,
+    // Steps at which a goal is reached only once, before the cycle starts
+    transient_hits: Vec<usize>,
+    // Steps within the first pass of the cycle at which a goal is reached,
+    // they repeat every period
+    periodic_hits: Vec<usize>,
+}
 
-    let file_content = fs::read_to_string(input_filepath).expect("File could not be loaded");
-    let mut directions = Vec::new();
+impl GhostCycle {
+    fn hits_at(&self, steps: usize) -> bool {
+        if steps < self.pre_period {
+            return self.transient_hits.contains(&steps);
+        }
+        self.periodic_hits
+            .iter()
+            .any(|hit| steps >= *hit && (steps - hit).is_multiple_of(self.period))
+    }
+}
 
-    let mut map: HashMap<_, _> = HashMap::new();
+#[derive(Debug, PartialEq, Eq)]
+enum GhostWalkError {
+    NoStartNodes,
+    NeverArrives(String),
+    NoSimultaneousArrival,
+    Overflow,
+}
 
-    for (idx, line) in file_content.split("\n").enumerate() {
-        if idx == 0 {
-            directions = parse_instructions(line);
-            continue;
+impl fmt::Display for GhostWalkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GhostWalkError::NoStartNodes => write!(f, "There are no start nodes"),
+            GhostWalkError::NeverArrives(name) => {
+                write!(f, "Ghost starting at {} never reaches a goal", name)
+            }
+            GhostWalkError::NoSimultaneousArrival => {
+                write!(f, "Ghosts never stand on goals at the same time")
+            }
+            GhostWalkError::Overflow => write!(f, "Number of steps does not fit into u128"),
         }
-        add_crossroad_to_map(&mut map, line);
     }
+}
 
-    if let Some(next_steps) = map.get("AAA") {
-        //for d in directions.iter().cycle() {
-        let (mut left, mut right) = next_steps.clone();
-        //for d in directions.iter() {
+impl Network {
+    fn ghost_cycle(&self, start: usize, is_goal: &[bool]) -> GhostCycle {
+        let instructions_count = self.instructions.len();
+        let mut first_seen = vec![usize::MAX; self.nodes.len() * instructions_count];
+        let mut hits = Vec::new();
+
+        let mut node = start;
         let mut steps = 0;
-        for d in directions.iter().cycle() {
+        loop {
+            let state = node * instructions_count + steps % instructions_count;
+            if first_seen[state] != usize::MAX {
+                let pre_period = first_seen[state];
+                let (transient_hits, periodic_hits) =
+                    hits.into_iter().partition(|hit| *hit < pre_period);
+                return GhostCycle {
+                    pre_period,
+                    period: steps - pre_period,
+                    transient_hits,
+                    periodic_hits,
+                };
+            }
+            first_seen[state] = steps;
+            if is_goal[node] {
+                hits.push(steps);
+            }
+            node = self.step(node, self.instructions[steps % instructions_count]);
             steps += 1;
-            match d {
-                Direction::Left => {
-                    println!("Going left to: {}", left);
-                    if left == "ZZZ" {
-                        println!("Finished");
-                        break;
-                    }
-                    match map.get(&left) {
-                        Some((l, r)) => {
-                            left = l.to_string();
-                            right = r.to_string();
-                        }
-                        None => (),
-                    }
-                }
-                Direction::Right => {
-                    println!("Going right to: {}", right);
-                    if right == "ZZZ" {
-                        println!("Finished");
-                        break;
-                    }
-                    match map.get(&right) {
-                        Some((l, r)) => {
-                            left = l.to_string();
-                            right = r.to_string();
-                        }
-                        None => (),
-                    }
+        }
+    }
+}
+
+// First step, at which every ghost stands on a goal
+fn simultaneous_arrival(cycles: &[GhostCycle]) -> Result<u128, GhostWalkError> {
+    // Before the longest pre-period, arrivals are one of the transient hits
+    let transient_arrival = cycles
+        .iter()
+        .flat_map(|cycle| cycle.transient_hits.iter())
+        .filter(|steps| **steps > 0 && cycles.iter().all(|cycle| cycle.hits_at(**steps)))
+        .min();
+    if let Some(steps) = transient_arrival {
+        return Ok(*steps as u128);
+    }
+
+    // Afterwards every ghost is in its cycle, so combine all periodic hits
+    let mut arrivals = vec![(0, 1)];
+    for cycle in cycles {
+        let mut combined = Vec::new();
+        for arrival in &arrivals {
+            for hit in &cycle.periodic_hits {
+                match math::crt(*arrival, (*hit as u128, cycle.period as u128)) {
+                    Ok(arrival) if !combined.contains(&arrival) => combined.push(arrival),
+                    Ok(_) | Err(CrtError::NoSolution) => (),
+                    Err(_) => return Err(GhostWalkError::Overflow),
                 }
             }
         }
+        arrivals = combined;
+    }
 
-        println!("Find finish in {} steps", steps);
+    let lower_bound = cycles
+        .iter()
+        .map(|cycle| cycle.pre_period)
+        .max()
+        .unwrap_or(0)
+        .max(1) as u128;
+    let mut first_arrival: Option<u128> = None;
+    for (residue, modulus) in arrivals {
+        let steps = lower_bound
+            .saturating_sub(residue)
+            .div_ceil(modulus)
+            .checked_mul(modulus)
+            .and_then(|offset| offset.checked_add(residue))
+            .ok_or(GhostWalkError::Overflow)?;
+        first_arrival = Some(first_arrival.map_or(steps, |first| first.min(steps)));
     }
+    first_arrival.ok_or(GhostWalkError::NoSimultaneousArrival)
 }
 
-pub fn day08_task02() {
-    let input_filepath = match std::env::current_dir() {
-        Ok(filepath) => filepath.join("input_d08_t01"),
-        //Ok(filepath) => filepath.join("input_d08_test3"),
-        Err(_) => panic!("Cannot find current directory"),
-    };
-
-    println!("Input filepath: {}", input_filepath.display());
+fn ghost_walk(
+    network: &Network,
+    start: &NodePattern,
+    goal: &NodePattern,
+) -> Result<u128, GhostWalkError> {
+    let starts = network.matching(start);
+    if starts.is_empty() {
+        return Err(GhostWalkError::NoStartNodes);
+    }
+    let is_goal = network.goals(&network.matching(goal));
 
-    let file_content = fs::read_to_string(input_filepath).expect("File could not be loaded");
-    let mut directions = Vec::new();
+    let mut cycles = Vec::new();
+    for start in starts {
+        let cycle = network.ghost_cycle(start, &is_goal);
+        if cycle.transient_hits.iter().all(|hit| *hit == 0) && cycle.periodic_hits.is_empty() {
+            return Err(GhostWalkError::NeverArrives(
+                network.names[start].to_string(),
+            ));
+        }
+        cycles.push(cycle);
+    }
+    simultaneous_arrival(&cycles)
+}
 
-    let mut map: HashMap<_, _> = HashMap::new();
+// Structural properties of the network relative to the start nodes
+struct NetworkAnalysis {
+    starts: Vec<usize>,
+    unreachable: Vec<usize>,
+    // Defined nodes whose both roads lead back to themselves
+    dead_ends: Vec<usize>,
+    // Strongly connected components with more than one node, or with a
+    // defined node which leads to itself
+    components: Vec<Vec<usize>>,
+    // Nodes used as a target, but never defined
+    missing: Vec<usize>,
+}
 
-    for (idx, line) in file_content.split("\n").enumerate() {
-        if idx == 0 {
-            directions = parse_instructions(line);
-            continue;
+impl Network {
+    fn reachable_from(&self, starts: &[usize]) -> Vec<bool> {
+        let mut reachable = vec![false; self.nodes.len()];
+        let mut to_visit = starts.to_vec();
+        while let Some(node) = to_visit.pop() {
+            if reachable[node] {
+                continue;
+            }
+            reachable[node] = true;
+            let (left, right) = self.nodes[node];
+            to_visit.extend([left, right]);
         }
-        add_crossroad_to_map(&mut map, line);
+        reachable
     }
 
-    let traversing_nodes: Vec<String> = map
-        .clone()
-        .into_keys()
-        .filter(|k| k.ends_with("A"))
-        .collect();
+    // Tarjan's algorithm with an explicit stack instead of recursion
+    fn strongly_connected_components(&self) -> Vec<Vec<usize>> {
+        let nodes_count = self.nodes.len();
+        let mut index = vec![usize::MAX; nodes_count];
+        let mut low_link = vec![0; nodes_count];
+        let mut on_stack = vec![false; nodes_count];
+        let mut stack = Vec::new();
+        let mut components = Vec::new();
+        let mut next_index = 0;
 
-    let mut calculated_lcm = 1;
+        for root in 0..nodes_count {
+            if index[root] != usize::MAX {
+                continue;
+            }
+            // (node, number of already visited roads)
+            let mut call_stack = vec![(root, 0)];
+            while let Some((node, visited_roads)) = call_stack.pop() {
+                if visited_roads == 0 {
+                    index[node] = next_index;
+                    low_link[node] = next_index;
+                    next_index += 1;
+                    stack.push(node);
+                    on_stack[node] = true;
+                }
 
-    for n in &traversing_nodes {
-        if let Some(next_steps) = map.get(n) {
-            let (mut left, mut right) = next_steps.clone();
-            let mut steps = 0;
-            for d in directions.iter().cycle() {
-                steps += 1;
-                match d {
-                    Direction::Left => {
-                        if left.ends_with("Z") {
-                            break;
-                        }
-                        match map.get(&left) {
-                            Some((l, r)) => {
-                                left = l.to_string();
-                                right = r.to_string();
-                            }
-                            None => (),
-                        }
+                let (left, right) = self.nodes[node];
+                if let Some(target) = [left, right].get(visited_roads) {
+                    call_stack.push((node, visited_roads + 1));
+                    if index[*target] == usize::MAX {
+                        call_stack.push((*target, 0));
+                    } else if on_stack[*target] {
+                        low_link[node] = low_link[node].min(index[*target]);
                     }
-                    Direction::Right => {
-                        if right.ends_with("Z") {
+                    continue;
+                }
+
+                if low_link[node] == index[node] {
+                    let mut component = Vec::new();
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == node {
                             break;
                         }
-                        match map.get(&right) {
-                            Some((l, r)) => {
-                                left = l.to_string();
-                                right = r.to_string();
-                            }
-                            None => (),
-                        }
                     }
+                    components.push(component);
+                }
+                if let Some((parent, _)) = call_stack.last() {
+                    low_link[*parent] = low_link[*parent].min(low_link[node]);
+                }
+            }
+        }
+        components
+    }
+
+    fn analyse(&self) -> NetworkAnalysis {
+        let starts = self.matching(&NodePattern::Suffix("A".to_string()));
+        let reachable = self.reachable_from(&starts);
+        let by_name =
+            |nodes: &mut Vec<usize>| nodes.sort_by(|a, b| self.names[*a].cmp(&self.names[*b]));
+
+        let mut unreachable: Vec<_> = (0..self.nodes.len())
+            .filter(|node| !reachable[*node])
+            .collect();
+        let mut dead_ends: Vec<_> = (0..self.nodes.len())
+            .filter(|node| self.defined[*node] && self.nodes[*node] == (*node, *node))
+            .collect();
+        let mut missing: Vec<_> = (0..self.nodes.len())
+            .filter(|node| !self.defined[*node])
+            .collect();
+        by_name(&mut unreachable);
+        by_name(&mut dead_ends);
+        by_name(&mut missing);
+
+        let mut components: Vec<_> = self
+            .strongly_connected_components()
+            .into_iter()
+            .filter(|component| {
+                let (node, (left, right)) = (component[0], self.nodes[component[0]]);
+                component.len() > 1 || (self.defined[node] && (left == node || right == node))
+            })
+            .collect();
+        components.iter_mut().for_each(by_name);
+        components.sort_by(|a, b| self.names[a[0]].cmp(&self.names[b[0]]));
+
+        NetworkAnalysis {
+            starts,
+            unreachable,
+            dead_ends,
+            components,
+            missing,
+        }
+    }
+
+    fn names_of(&self, nodes: &[usize]) -> String {
+        let names: Vec<_> = nodes
+            .iter()
+            .map(|node| self.names[*node].as_str())
+            .collect();
+        names.join(", ")
+    }
+
+    fn analysis_report(&self, analysis: &NetworkAnalysis) -> String {
+        let mut report = format!(
+            "{} nodes, {} start nodes: {}\n",
+            self.nodes.len(),
+            analysis.starts.len(),
+            self.names_of(&analysis.starts)
+        );
+        report += &format!(
+            "Unreachable from any start ({}): {}\n",
+            analysis.unreachable.len(),
+            self.names_of(&analysis.unreachable)
+        );
+        report += &format!(
+            "Dead-end self loops ({}): {}\n",
+            analysis.dead_ends.len(),
+            self.names_of(&analysis.dead_ends)
+        );
+        report += &format!(
+            "Missing target nodes ({}): {}\n",
+            analysis.missing.len(),
+            self.names_of(&analysis.missing)
+        );
+        report += &format!(
+            "Strongly connected components ({}):\n",
+            analysis.components.len()
+        );
+        for component in &analysis.components {
+            report += &format!(
+                "    {} nodes: {}\n",
+                component.len(),
+                self.names_of(component)
+            );
+        }
+        report
+    }
+
+    fn network_dot(&self, analysis: &NetworkAnalysis) -> String {
+        let unreachable: HashSet<_> = analysis.unreachable.iter().copied().collect();
+        let mut dot = String::from("digraph network {\n");
+        for (node, name) in self.names.iter().enumerate() {
+            let style = if !self.defined[node] {
+                ", style=dashed, color=red"
+            } else if name.ends_with('A') {
+                ", style=filled, fillcolor=palegreen"
+            } else if name.ends_with('Z') {
+                ", style=filled, fillcolor=salmon"
+            } else if unreachable.contains(&node) {
+                ", color=gray"
+            } else {
+                ""
+            };
+            dot += &format!("    \"{}\" [label=\"{}\"{}];\n", name, name, style);
+        }
+        for (node, (left, right)) in self.nodes.iter().enumerate() {
+            if !self.defined[node] {
+                continue;
+            }
+            match left == right {
+                true => {
+                    dot += &format!(
+                        "    \"{}\" -> \"{}\" [label=\"LR\"];\n",
+                        self.names[node], self.names[*left]
+                    )
+                }
+                false => {
+                    dot += &format!(
+                        "    \"{}\" -> \"{}\" [label=\"L\"];\n",
+                        self.names[node], self.names[*left]
+                    );
+                    dot += &format!(
+                        "    \"{}\" -> \"{}\" [label=\"R\"];\n",
+                        self.names[node], self.names[*right]
+                    );
                 }
             }
+        }
+        dot += "}\n";
+        dot
+    }
+}
+
+fn load_network(file_path: &Path) -> Network {
+    let file_content = fs::read_to_string(file_path).expect("File could not be loaded");
+    match Network::from_str(&file_content) {
+        Ok(network) => network,
+        Err(err) => panic!("{}", err),
+    }
+}
 
-            calculated_lcm = lcm(calculated_lcm, steps);
-            println!("Find finish in {} steps for {}", steps, n);
+pub fn solve_part_01(file_path: &Path) -> usize {
+    let network = load_network(file_path);
+    let start = network.node("AAA").expect("Missing node AAA");
+    let goal = network.node("ZZZ").expect("Missing node ZZZ");
+    network
+        .steps_until(start, &network.goals(&[goal]), &network.instructions)
+        .expect("ZZZ cannot be reached")
+}
+
+pub fn solve_part_02(file_path: &Path) -> u128 {
+    let start = NodePattern::Suffix("A".to_string());
+    let goal = NodePattern::Suffix("Z".to_string());
+    match ghost_walk(&load_network(file_path), &start, &goal) {
+        Ok(steps) => steps,
+        Err(err) => panic!("{}", err),
+    }
+}
+
+// Walks from every node matching the start pattern to the first node
+// matching the goal pattern, using the network's own instructions unless
+// others are given
+fn walks_report(
+    network: &Network,
+    start: &NodePattern,
+    goal: &NodePattern,
+    instructions: Option<&str>,
+    show_path: bool,
+) -> Result<String, NetworkError> {
+    let instructions = match instructions {
+        Some(instructions) => parse_instructions(instructions)?,
+        None => network.instructions.clone(),
+    };
+    if instructions.is_empty() {
+        return Err(NetworkError::MissingInstructions);
+    }
+    let is_goal = network.goals(&network.matching(goal));
+
+    let mut report = String::new();
+    for start in network.matching(start) {
+        match network.walk_path(start, &is_goal, &instructions) {
+            Some(path) => {
+                report += &format!(
+                    "{} -> {} in {} steps\n",
+                    network.names[start],
+                    network.names[path[path.len() - 1]],
+                    path.len() - 1
+                );
+                if show_path {
+                    report += &format!("    {}\n", network.names_of(&path));
+                }
+            }
+            None => report += &format!("{} never reaches a goal\n", network.names[start]),
         }
     }
-    println!("LCM: {}", calculated_lcm);
+    Ok(report)
+}
+
+pub fn print_walks(
+    file_path: &Path,
+    start: &str,
+    goal: &str,
+    instructions: Option<&str>,
+    show_path: bool,
+) {
+    let network = load_network(file_path);
+    let start = NodePattern::parse(start).expect("Unknown start pattern");
+    let goal = NodePattern::parse(goal).expect("Unknown goal pattern");
+    match walks_report(&network, &start, &goal, instructions, show_path) {
+        Ok(report) => print!("{}", report),
+        Err(err) => println!("{}", err),
+    }
+}
+
+pub fn print_network(file_path: &Path, as_dot: bool) {
+    let network = load_network(file_path);
+    let analysis = network.analyse();
+    match as_dot {
+        true => print!("{}", network.network_dot(&analysis)),
+        false => print!("{}", network.analysis_report(&analysis)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ghosts(network: &Network) -> Result<u128, GhostWalkError> {
+        let start = NodePattern::Suffix("A".to_string());
+        let goal = NodePattern::Suffix("Z".to_string());
+        ghost_walk(network, &start, &goal)
+    }
+
+    #[test]
+    fn test_interning_nodes() {
+        let network = Network::from_str("LR\n\nAAA = (BBB, CCC)\nBBB = (AAA, AAA)\n").unwrap();
+        assert_eq!(network.names, vec!["AAA", "BBB", "CCC"]);
+        assert_eq!(network.nodes, vec![(1, 2), (0, 0), (2, 2)]);
+        assert_eq!(network.defined, vec![true, true, false]);
+        assert_eq!(network.step(0, Direction::Right), 2);
+    }
+
+    #[test]
+    fn test_network_validation() {
+        assert_eq!(
+            Network::from_str("LX\n\nAAA = (AAA, AAA)\n").err(),
+            Some(NetworkError::InvalidInstruction('X'))
+        );
+        assert_eq!(
+            Network::from_str("L\n\nAAA = (AAA, AAA)\nAAA = (AAA, AAA)\n").err(),
+            Some(NetworkError::DuplicatedNode("AAA".to_string()))
+        );
+        assert_eq!(
+            Network::from_str("L\n\nAAA = AAA, AAA\n").err(),
+            Some(NetworkError::InvalidLine("AAA = AAA, AAA".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_unreachable_goal() {
+        let network = Network::from_str("L\n\nAAA = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)\n").unwrap();
+        assert_eq!(
+            network.steps_until(0, &network.goals(&[1]), &network.instructions),
+            None
+        );
+    }
+
+    #[test]
+    fn test_ghost_cycle() {
+        let network =
+            Network::from_str("L\n\n1A = (1B, 1B)\n1B = (1Z, 1Z)\n1Z = (1B, 1B)\n").unwrap();
+        let is_goal = network.goals(&network.matching(&NodePattern::Suffix("Z".to_string())));
+        assert_eq!(
+            network.ghost_cycle(0, &is_goal),
+            GhostCycle {
+                pre_period: 1,
+                period: 2,
+                transient_hits: vec![],
+                periodic_hits: vec![2],
+            }
+        );
+    }
+
+    #[test]
+    fn test_offset_ghosts() {
+        // First ghost arrives at even steps, the second one at 1, 4, 7, ...
+        let network = Network::from_str(
+            "L\n\n1A = (1B, 1B)\n1B = (1Z, 1Z)\n1Z = (1B, 1B)\n\
+             2A = (2Z, 2Z)\n2Z = (2C, 2C)\n2C = (2D, 2D)\n2D = (2Z, 2Z)\n",
+        )
+        .unwrap();
+        assert_eq!(ghosts(&network), Ok(4));
+    }
+
+    #[test]
+    fn test_transient_arrival() {
+        // First ghost stands on a goal only after the first step
+        let network = Network::from_str(
+            "L\n\n1A = (1Z, 1Z)\n1Z = (XX, XX)\nXX = (XX, XX)\n2A = (2Z, 2Z)\n2Z = (2Z, 2Z)\n",
+        )
+        .unwrap();
+        assert_eq!(ghosts(&network), Ok(1));
+    }
+
+    #[test]
+    fn test_ghosts_never_meet() {
+        let network = Network::from_str(
+            "L\n\n1A = (1B, 1B)\n1B = (1Z, 1Z)\n1Z = (1B, 1B)\n\
+             2A = (2Z, 2Z)\n2Z = (2C, 2C)\n2C = (2Z, 2Z)\n",
+        )
+        .unwrap();
+        assert_eq!(ghosts(&network), Err(GhostWalkError::NoSimultaneousArrival));
+
+        let network = Network::from_str("L\n\n1A = (XX, XX)\nXX = (XX, XX)\n").unwrap();
+        assert_eq!(
+            ghosts(&network),
+            Err(GhostWalkError::NeverArrives("1A".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_network_analysis() {
+        let network = Network::from_str(
+            "L\n\n1A = (1B, 1C)\n1B = (1C, 1A)\n1C = (1Z, QQ)\n1Z = (1Z, 1Z)\n\
+             XX = (YY, 1A)\nYY = (XX, XX)\n",
+        )
+        .unwrap();
+        let analysis = network.analyse();
+        let names = |nodes: &[usize]| network.names_of(nodes);
+        assert_eq!(names(&analysis.starts), "1A");
+        assert_eq!(names(&analysis.unreachable), "XX, YY");
+        assert_eq!(names(&analysis.dead_ends), "1Z");
+        assert_eq!(names(&analysis.missing), "QQ");
+        let components: Vec<_> = analysis.components.iter().map(|c| names(c)).collect();
+        assert_eq!(components, vec!["1A, 1B", "1Z", "XX, YY"]);
+    }
+
+    #[test]
+    fn test_network_dot() {
+        let network = Network::from_str("LR\n\n1A = (1Z, QQ)\n1Z = (1Z, 1Z)\n").unwrap();
+        assert_eq!(
+            network.network_dot(&network.analyse()),
+            "digraph network {\n    \
+                \"1A\" [label=\"1A\", style=filled, fillcolor=palegreen];\n    \
+                \"1Z\" [label=\"1Z\", style=filled, fillcolor=salmon];\n    \
+                \"QQ\" [label=\"QQ\", style=dashed, color=red];\n    \
+                \"1A\" -> \"1Z\" [label=\"L\"];\n    \
+                \"1A\" -> \"QQ\" [label=\"R\"];\n    \
+                \"1Z\" -> \"1Z\" [label=\"LR\"];\n\
+            }\n"
+        );
+    }
+
+    #[test]
+    fn test_node_patterns() {
+        let pattern = |pattern: &str| NodePattern::parse(pattern).unwrap();
+        assert_eq!(pattern("AAA"), NodePattern::Exact("AAA".to_string()));
+        assert_eq!(NodePattern::parse("regex:A+"), None);
+        assert!(pattern("suffix:Z").matches("XYZ"));
+        assert!(!pattern("exact:XY").matches("XYZ"));
+        assert!(pattern("glob:?Y*").matches("XYZ"));
+        assert!(pattern("glob:*Z").matches("XYZ"));
+        assert!(pattern("glob:*").matches(""));
+        assert!(pattern("glob:X*Y*Z").matches("XAYBYZ"));
+        assert!(!pattern("glob:X?Z").matches("XYYZ"));
+        assert!(!pattern("glob:*Y").matches("XYZ"));
+    }
+
+    #[test]
+    fn test_walks_report() {
+        let network =
+            Network::from_str("L\n\n1A = (1B, 1Z)\n1B = (1Z, 1A)\n1Z = (1Z, 1Z)\n2A = (2A, 1Z)\n")
+                .unwrap();
+        let start = NodePattern::parse("glob:?A").unwrap();
+        let goal = NodePattern::parse("1Z").unwrap();
+        assert_eq!(
+            walks_report(&network, &start, &goal, None, true).unwrap(),
+            "1A -> 1Z in 2 steps\n    1A, 1B, 1Z\n2A never reaches a goal\n"
+        );
+        assert_eq!(
+            walks_report(&network, &start, &goal, Some("RL"), false).unwrap(),
+            "1A -> 1Z in 1 steps\n2A -> 1Z in 1 steps\n"
+        );
+        assert_eq!(
+            walks_report(&network, &start, &goal, Some("LX"), false).err(),
+            Some(NetworkError::InvalidInstruction('X'))
+        );
+    }
+
+    #[test]
+    fn test_solving_d08_part1() {
+        let input_filepath = match std::env::current_dir() {
+            Ok(filepath) => filepath.join("input_d08_test"),
+            Err(_) => panic!("Cannot find current directory"),
+        };
+        assert_eq!(solve_part_01(&input_filepath), 2);
+
+        let input_filepath = match std::env::current_dir() {
+            Ok(filepath) => filepath.join("input_d08_test2"),
+            Err(_) => panic!("Cannot find current directory"),
+        };
+        assert_eq!(solve_part_01(&input_filepath), 6);
+    }
+
+    #[test]
+    fn test_solving_d08_part2() {
+        let input_filepath = match std::env::current_dir() {
+            Ok(filepath) => filepath.join("input_d08_test3"),
+            Err(_) => panic!("Cannot find current directory"),
+        };
+        assert_eq!(solve_part_02(&input_filepath), 6);
+    }
 }
@@ -1,104 +1,463 @@
-use std::fs;
+use core::fmt;
+use std::{fs, path::Path};
+
+use crate::math;
 
 fn parse_row_to_sequence(line: &str) -> Vec<i64> {
-    let mut v = Vec::new();
+    line.split_whitespace()
+        .filter_map(|str_val| str_val.parse::<i64>().ok())
+        .collect()
+}
 
-    for str_val in line.split(" ") {
-        match str_val.parse::<i64>() {
-            Ok(num_val) => v.push(num_val),
-            Err(_) => (),
+#[derive(Debug, PartialEq, Eq)]
+enum ExtrapolationError {
+    // Fewer than three values cannot show that any difference row is zero,
+    // unless the sequence is constant
+    TooShort,
+    // Differences never became zero, although the sequence was long enough
+    // to recognise polynomials up to max_degree
+    NotPolynomial { max_degree: usize },
+    Overflow,
+}
+
+impl fmt::Display for ExtrapolationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtrapolationError::TooShort => write!(f, "Sequence is too short"),
+            ExtrapolationError::NotPolynomial { max_degree } => write!(
+                f,
+                "Sequence is not generated by a polynomial of degree at most {}",
+                max_degree
+            ),
+            ExtrapolationError::Overflow => write!(f, "Value does not fit into i64"),
         }
     }
+}
 
-    v
+// Sequence together with its repeated differences, down to the first row
+// which consists only of zeros
+struct DifferenceTable {
+    rows: Vec<Vec<i64>>,
 }
 
-fn find_next_value(v: &Vec<i64>) -> i64 {
-    let mut differences: Vec<Vec<i64>> = Vec::new();
-    differences.push(v.clone());
+impl DifferenceTable {
+    fn new(sequence: &[i64]) -> Result<DifferenceTable, ExtrapolationError> {
+        if sequence.len() < 2 {
+            return Err(ExtrapolationError::TooShort);
+        }
+        let mut rows = vec![sequence.to_vec()];
+        loop {
+            let last_row = &rows[rows.len() - 1];
+            if last_row.iter().all(|value| *value == 0) {
+                return Ok(DifferenceTable { rows });
+            }
+            if last_row.len() == 1 {
+                return Err(match sequence.len() - 2 {
+                    0 => ExtrapolationError::TooShort,
+                    max_degree => ExtrapolationError::NotPolynomial { max_degree },
+                });
+            }
+            let differences = last_row
+                .windows(2)
+                .map(|pair| pair[1].checked_sub(pair[0]))
+                .collect::<Option<Vec<_>>>()
+                .ok_or(ExtrapolationError::Overflow)?;
+            rows.push(differences);
+        }
+    }
 
-    loop {
-        let prev_vec = differences.last().unwrap();
-        let mut next_differences = Vec::new();
+    // Degree of the polynomial generating the sequence, a zero sequence is
+    // treated as a constant
+    fn degree(&self) -> usize {
+        self.rows.len().saturating_sub(2)
+    }
 
-        let mut prev_item = prev_vec[0];
-        for i in 1..prev_vec.len() {
-            let current_item = prev_vec[i];
-            next_differences.push(current_item - prev_item);
-            prev_item = current_item;
+    // Next k values after the end of the sequence
+    fn extrapolate_forward(&self, k: usize) -> Result<Vec<i64>, ExtrapolationError> {
+        let mut last_values: Vec<_> = self.rows.iter().map(|row| row[row.len() - 1]).collect();
+        let mut values = Vec::with_capacity(k);
+        for _ in 0..k {
+            for row_idx in (0..last_values.len() - 1).rev() {
+                last_values[row_idx] = last_values[row_idx]
+                    .checked_add(last_values[row_idx + 1])
+                    .ok_or(ExtrapolationError::Overflow)?;
+            }
+            values.push(last_values[0]);
         }
+        Ok(values)
+    }
+
+    // Previous k values before the start of the sequence, nearest first
+    fn extrapolate_backward(&self, k: usize) -> Result<Vec<i64>, ExtrapolationError> {
+        let mut first_values: Vec<_> = self.rows.iter().map(|row| row[0]).collect();
+        let mut values = Vec::with_capacity(k);
+        for _ in 0..k {
+            for row_idx in (0..first_values.len() - 1).rev() {
+                first_values[row_idx] = first_values[row_idx]
+                    .checked_sub(first_values[row_idx + 1])
+                    .ok_or(ExtrapolationError::Overflow)?;
+            }
+            values.push(first_values[0]);
+        }
+        Ok(values)
+    }
 
-        let number_of_zeros = next_differences.iter().clone().filter(|v| **v == 0).count();
-        differences.push(next_differences.clone());
-        if number_of_zeros == next_differences.len() {
-            break;
+    // Value at any index, where 0 is the first element of the sequence,
+    // using Newton's forward formula f(x) = sum C(x, j) * delta^j f(0)
+    fn value_at(&self, index: i64) -> Result<i64, ExtrapolationError> {
+        let mut value = 0i128;
+        let mut binomial = 1i128;
+        for (j, row) in self.rows.iter().enumerate().take(self.degree() + 1) {
+            if j > 0 {
+                // C(x, j) = C(x, j - 1) * (x - j + 1) / j is always exact
+                binomial = binomial
+                    .checked_mul(index as i128 - j as i128 + 1)
+                    .ok_or(ExtrapolationError::Overflow)?
+                    / j as i128;
+            }
+            value = binomial
+                .checked_mul(row[0] as i128)
+                .and_then(|term| value.checked_add(term))
+                .ok_or(ExtrapolationError::Overflow)?;
         }
+        i64::try_from(value).map_err(|_| ExtrapolationError::Overflow)
+    }
+}
+
+fn load_sequences(file_path: &Path) -> Vec<Vec<i64>> {
+    let file_content = fs::read_to_string(file_path).expect("File could not be loaded");
+    file_content
+        .lines()
+        .map(parse_row_to_sequence)
+        .filter(|sequence| !sequence.is_empty())
+        .collect()
+}
+
+// Binomial coefficients C(n, 0), ..., C(n, n). C(n, k + 1) = C(n, k) * (n - k) / (k + 1)
+// is computed with the common factor of C(n, k) and k + 1 divided out first, so
+// it only fails once a coefficient itself does not fit into i128, for n > 130.
+fn binomials(n: usize) -> Result<Vec<i128>, ExtrapolationError> {
+    let mut binomials = vec![1i128];
+    for k in 0..n {
+        let divisor = (k + 1) as u128;
+        let common = math::gcd(binomials[k] as u128, divisor);
+        let next = (binomials[k] / common as i128)
+            .checked_mul(((n - k) as u128 / (divisor / common)) as i128)
+            .ok_or(ExtrapolationError::Overflow)?;
+        binomials.push(next);
     }
+    Ok(binomials)
+}
 
-    let mut prev_last_item = 0;
-    let mut last_item = 0;
-    for v in differences.iter_mut().rev() {
-        last_item = v.last().unwrap() + prev_last_item;
-        v.push(last_item);
-        prev_last_item = *v.last().unwrap();
+fn closed_form_sum(terms: impl Iterator<Item = (i128, i64)>) -> Result<i64, ExtrapolationError> {
+    let mut sum = 0i128;
+    for (coefficient, value) in terms {
+        sum = coefficient
+            .checked_mul(value as i128)
+            .and_then(|term| sum.checked_add(term))
+            .ok_or(ExtrapolationError::Overflow)?;
     }
+    i64::try_from(sum).map_err(|_| ExtrapolationError::Overflow)
+}
 
-    last_item
+// n-th difference of the polynomial through all n values is zero, so
+// f(n) = sum over i of (-1)^(n - 1 - i) * C(n, i) * f(i), computed in O(n).
+// Unlike the difference table, this cannot tell when there is no polynomial
+// of degree below n - 1 and simply interpolates through all the values.
+// Histories longer than 130 values need binomials beyond i128 and report
+// Overflow, even when the difference table can still extrapolate them.
+fn next_value_closed_form(sequence: &[i64]) -> Result<i64, ExtrapolationError> {
+    if sequence.is_empty() {
+        return Err(ExtrapolationError::TooShort);
+    }
+    let n = sequence.len();
+    let binomials = binomials(n)?;
+    closed_form_sum(sequence.iter().enumerate().map(|(i, value)| {
+        let sign = if (n - 1 - i).is_multiple_of(2) { 1 } else { -1 };
+        (sign * binomials[i], *value)
+    }))
 }
 
-fn find_prev_value(v: &Vec<i64>) -> i64 {
-    let mut differences: Vec<Vec<i64>> = Vec::new();
-    differences.push(v.clone());
+// f(-1) = sum over i of (-1)^i * C(n, i + 1) * f(i)
+fn previous_value_closed_form(sequence: &[i64]) -> Result<i64, ExtrapolationError> {
+    if sequence.is_empty() {
+        return Err(ExtrapolationError::TooShort);
+    }
+    let binomials = binomials(sequence.len())?;
+    closed_form_sum(sequence.iter().enumerate().map(|(i, value)| {
+        let sign = if i.is_multiple_of(2) { 1 } else { -1 };
+        (sign * binomials[i + 1], *value)
+    }))
+}
 
-    loop {
-        let prev_vec = differences.last().unwrap();
-        let mut next_differences = Vec::new();
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    DifferenceTable,
+    ClosedForm,
+}
 
-        let mut prev_item = prev_vec[0];
-        for i in 1..prev_vec.len() {
-            let current_item = prev_vec[i];
-            next_differences.push(current_item - prev_item);
-            prev_item = current_item;
+pub fn parse_method(method: &str) -> Option<Method> {
+    match method {
+        "table" => Some(Method::DifferenceTable),
+        "closed-form" => Some(Method::ClosedForm),
+        _ => None,
+    }
+}
+
+fn next_value(sequence: &[i64], method: Method) -> Result<i64, ExtrapolationError> {
+    match method {
+        Method::DifferenceTable => Ok(DifferenceTable::new(sequence)?.extrapolate_forward(1)?[0]),
+        Method::ClosedForm => next_value_closed_form(sequence),
+    }
+}
+
+fn previous_value(sequence: &[i64], method: Method) -> Result<i64, ExtrapolationError> {
+    match method {
+        Method::DifferenceTable => Ok(DifferenceTable::new(sequence)?.extrapolate_backward(1)?[0]),
+        Method::ClosedForm => previous_value_closed_form(sequence),
+    }
+}
+
+fn sum_extrapolated(
+    file_path: &Path,
+    extrapolate: impl Fn(&[i64]) -> Result<i64, ExtrapolationError>,
+) -> i64 {
+    let mut sum = 0i64;
+    for sequence in load_sequences(file_path) {
+        let value = extrapolate(&sequence)
+            .and_then(|value| sum.checked_add(value).ok_or(ExtrapolationError::Overflow));
+        match value {
+            Ok(value) => sum = value,
+            Err(err) => panic!("{}: {:?}", err, sequence),
         }
+    }
+    sum
+}
+
+pub fn sum_next_values(file_path: &Path, method: Method) -> i64 {
+    sum_extrapolated(file_path, |sequence| next_value(sequence, method))
+}
+
+pub fn sum_previous_values(file_path: &Path, method: Method) -> i64 {
+    sum_extrapolated(file_path, |sequence| previous_value(sequence, method))
+}
+
+pub fn solve_part_01(file_path: &Path) -> i64 {
+    sum_next_values(file_path, Method::DifferenceTable)
+}
+
+pub fn solve_part_02(file_path: &Path) -> i64 {
+    sum_previous_values(file_path, Method::DifferenceTable)
+}
 
-        let number_of_zeros = next_differences.iter().clone().filter(|v| **v == 0).count();
-        differences.push(next_differences.clone());
-        if number_of_zeros == next_differences.len() {
-            break;
+pub fn print_extrapolation(file_path: &Path, steps: usize) {
+    for sequence in load_sequences(file_path) {
+        let extrapolation = DifferenceTable::new(&sequence).and_then(|table| {
+            let mut previous = table.extrapolate_backward(steps)?;
+            previous.reverse();
+            Ok((table.degree(), previous, table.extrapolate_forward(steps)?))
+        });
+        match extrapolation {
+            Ok((degree, previous, next)) => println!(
+                "degree {}: {:?} <- {:?} -> {:?}",
+                degree, previous, sequence, next
+            ),
+            Err(err) => println!("{}: {:?}", err, sequence),
         }
     }
+}
 
-    let mut prev_first = 0;
-    let mut first_item = 0;
-    for v in differences.iter_mut().rev() {
-        let first = *v.first().unwrap();
-        first_item = first - prev_first;
-        v.insert(0, first_item);
-        prev_first = first_item;
+pub fn print_values_at(file_path: &Path, index: i64) {
+    let mut sum = Ok(0i64);
+    for sequence in load_sequences(file_path) {
+        let value = DifferenceTable::new(&sequence)
+            .and_then(|table| Ok((table.degree(), table.value_at(index)?)));
+        match value {
+            Ok((degree, value)) => {
+                println!("degree {}: value at {} is {}", degree, index, value);
+                sum = sum.and_then(|sum: i64| {
+                    sum.checked_add(value).ok_or(ExtrapolationError::Overflow)
+                });
+            }
+            Err(err) => {
+                println!("{}: {:?}", err, sequence);
+                sum = Err(err);
+            }
+        }
     }
+    match sum {
+        Ok(sum) => println!("Sum of values at {} is: {}", index, sum),
+        Err(err) => println!("Sum of values at {} is unknown: {}", index, err),
+    }
+}
 
-    first_item
+// Compares both methods on every sequence and prints the disagreements
+pub fn cross_check(file_path: &Path) {
+    let sequences = load_sequences(file_path);
+    let mut mismatches = 0;
+    for sequence in &sequences {
+        for (direction, extrapolate) in [
+            ("next", next_value as fn(&[i64], Method) -> _),
+            ("previous", previous_value),
+        ] {
+            let by_table = extrapolate(sequence, Method::DifferenceTable);
+            let by_closed_form = extrapolate(sequence, Method::ClosedForm);
+            if by_table != by_closed_form {
+                mismatches += 1;
+                println!(
+                    "{:?}: {} value {:?} by difference table, {:?} by closed form",
+                    sequence, direction, by_table, by_closed_form
+                );
+            }
+        }
+    }
+    println!("{} mismatches in {} sequences", mismatches, sequences.len());
 }
-pub fn day09() {
-    let input_filepath = match std::env::current_dir() {
-        //Ok(filepath) => filepath.join("input_d09_test"),
-        Ok(filepath) => filepath.join("input_d09_t01"),
-        Err(_) => panic!("Cannot find current directory"),
-    };
 
-    println!("Input filepath: {}", input_filepath.display());
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_difference_table() {
+        let table = DifferenceTable::new(&[10, 13, 16, 21, 30, 45]).unwrap();
+        assert_eq!(table.degree(), 3);
+        assert_eq!(table.extrapolate_forward(3), Ok(vec![68, 101, 146]));
+        assert_eq!(table.extrapolate_backward(2), Ok(vec![5, -4]));
+
+        let degree = |sequence: &[i64]| DifferenceTable::new(sequence).map(|table| table.degree());
+        assert_eq!(degree(&[0, 0, 0]), Ok(0));
+        assert_eq!(degree(&[4, 4]), Ok(0));
+        assert_eq!(degree(&[1, 3, 5]), Ok(1));
+    }
+
+    #[test]
+    fn test_newton_forward_formula() {
+        let table = DifferenceTable::new(&[10, 13, 16, 21, 30, 45]).unwrap();
+        for index in 0..6 {
+            assert_eq!(table.value_at(index), Ok(table.rows[0][index as usize]));
+        }
+        assert_eq!(
+            table.value_at(8),
+            Ok(table.extrapolate_forward(3).unwrap()[2])
+        );
+        assert_eq!(
+            table.value_at(-2),
+            Ok(table.extrapolate_backward(2).unwrap()[1])
+        );
+
+        // x^2 sampled at 0..3
+        let squares = DifferenceTable::new(&[0, 1, 4, 9]).unwrap();
+        assert_eq!(squares.value_at(-7), Ok(49));
+        assert_eq!(squares.value_at(1000), Ok(1_000_000));
+    }
 
-    let file_content = fs::read_to_string(input_filepath).expect("File could not be loaded");
+    #[test]
+    fn test_sequences_without_polynomial() {
+        assert_eq!(
+            DifferenceTable::new(&[]).err(),
+            Some(ExtrapolationError::TooShort)
+        );
+        assert_eq!(
+            DifferenceTable::new(&[7]).err(),
+            Some(ExtrapolationError::TooShort)
+        );
+        assert_eq!(
+            DifferenceTable::new(&[1, 3]).err(),
+            Some(ExtrapolationError::TooShort)
+        );
+        assert_eq!(DifferenceTable::new(&[3, 3]).unwrap().degree(), 0);
+        assert_eq!(
+            DifferenceTable::new(&[1, 2, 4, 8, 16]).err(),
+            Some(ExtrapolationError::NotPolynomial { max_degree: 3 })
+        );
+    }
+
+    #[test]
+    fn test_overflow_detection() {
+        assert_eq!(
+            DifferenceTable::new(&[i64::MIN, i64::MAX]).err(),
+            Some(ExtrapolationError::Overflow)
+        );
+        let table = DifferenceTable::new(&[i64::MAX - 2, i64::MAX - 1, i64::MAX]).unwrap();
+        assert_eq!(
+            table.extrapolate_forward(1),
+            Err(ExtrapolationError::Overflow)
+        );
+        assert_eq!(table.extrapolate_backward(1), Ok(vec![i64::MAX - 3]));
+        assert_eq!(table.value_at(3), Err(ExtrapolationError::Overflow));
+        assert_eq!(table.value_at(-1), Ok(i64::MAX - 3));
+
+        let quadratic = DifferenceTable::new(&[0, 0, 1, 3, 6]).unwrap();
+        assert_eq!(
+            quadratic.value_at(i64::MAX),
+            Err(ExtrapolationError::Overflow)
+        );
+    }
 
-    let mut sum = 0;
-    let mut prev_sum = 0;
-    for line in file_content.split("\n") {
-        let seq = parse_row_to_sequence(line);
-        if seq.len() > 0 {
-            sum += find_next_value(&seq);
-            prev_sum += find_prev_value(&seq);
+    #[test]
+    fn test_closed_form_matches_difference_table() {
+        // Values of 3x^3 - 5x^2 + x - 7 shifted to start at various points
+        let polynomial = |x: i64| 3 * x * x * x - 5 * x * x + x - 7;
+        for start in -20..20 {
+            for len in 5..12 {
+                let sequence: Vec<_> = (start..start + len).map(polynomial).collect();
+                for method in [Method::DifferenceTable, Method::ClosedForm] {
+                    assert_eq!(next_value(&sequence, method), Ok(polynomial(start + len)));
+                    assert_eq!(previous_value(&sequence, method), Ok(polynomial(start - 1)));
+                }
+            }
         }
     }
-    println!("Sum of next values is: {}", sum);
-    println!("Sum of prev values is: {}", prev_sum);
+
+    #[test]
+    fn test_closed_form_edge_cases() {
+        assert_eq!(
+            next_value_closed_form(&[]),
+            Err(ExtrapolationError::TooShort)
+        );
+        assert_eq!(next_value_closed_form(&[7]), Ok(7));
+        assert_eq!(previous_value_closed_form(&[7]), Ok(7));
+        // Interpolates through all values instead of rejecting the sequence
+        assert_eq!(next_value_closed_form(&[1, 3]), Ok(5));
+        assert_eq!(previous_value_closed_form(&[1, 3]), Ok(-1));
+        assert_eq!(
+            next_value_closed_form(&[i64::MAX - 1, i64::MAX]),
+            Err(ExtrapolationError::Overflow)
+        );
+        assert_eq!(binomials(4), Ok(vec![1, 4, 6, 4, 1]));
+        let largest = binomials(130).unwrap();
+        assert_eq!(largest[65], 95067625827960698145584333020095113100);
+        assert_eq!(largest[1], 130);
+        assert_eq!(largest[129], 130);
+        assert_eq!(binomials(131), Err(ExtrapolationError::Overflow));
+    }
+
+    #[test]
+    fn test_solving_d09_with_closed_form() {
+        let input_filepath = match std::env::current_dir() {
+            Ok(filepath) => filepath.join("input_d09_test"),
+            Err(_) => panic!("Cannot find current directory"),
+        };
+        assert_eq!(sum_next_values(&input_filepath, Method::ClosedForm), 114);
+        assert_eq!(sum_previous_values(&input_filepath, Method::ClosedForm), 2);
+    }
+
+    #[test]
+    fn test_solving_d09_part1() {
+        let input_filepath = match std::env::current_dir() {
+            Ok(filepath) => filepath.join("input_d09_test"),
+            Err(_) => panic!("Cannot find current directory"),
+        };
+        assert_eq!(solve_part_01(&input_filepath), 114);
+    }
+
+    #[test]
+    fn test_solving_d09_part2() {
+        let input_filepath = match std::env::current_dir() {
+            Ok(filepath) => filepath.join("input_d09_test"),
+            Err(_) => panic!("Cannot find current directory"),
+        };
+        assert_eq!(solve_part_02(&input_filepath), 2);
+    }
 }
@@ -1,13 +1,208 @@
+mod aoc10;
 mod aoc15;
+mod aoc2;
+mod aoc3;
+mod aoc4;
+mod aoc5;
+mod aoc6;
+mod aoc7;
+mod aoc8;
+mod aoc9;
+mod math;
+mod tokenizer;
 
-fn main() {
-    let input_filepath = match std::env::current_dir() {
-        Ok(filepath) => filepath.join("input_d15_01"),
+use std::path::PathBuf;
+
+fn input_path(file_name: &str) -> PathBuf {
+    match std::env::current_dir() {
+        Ok(filepath) => filepath.join(file_name),
         Err(_) => panic!("Cannot find current directory"),
-    };
-    let result = aoc15::solve_part_01(&input_filepath);
-    println!("And the result is {}", result);
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+
+    match args.as_slice() {
+        ["day2"] => {
+            aoc2::day02_task01();
+            aoc2::day02_task02();
+        }
+        ["day2", "query", rest @ ..] => {
+            let raise_by = match rest.first() {
+                Some(val) => val.parse::<u64>().expect("Cannot parse raise value"),
+                None => 1,
+            };
+            aoc2::day02_query(&input_path("input_d02_t01"), raise_by);
+        }
+        ["day3"] => {
+            let input_filepath = input_path("input_d03_t01");
+            println!("Sum: {}", aoc3::solve_part_01(&input_filepath));
+            println!("Gear ratios sum {}", aoc3::solve_part_02(&input_filepath));
+        }
+        ["day3", "--signed"] => {
+            let input_filepath = input_path("input_d03_t01");
+            println!("Sum: {}", aoc3::sum_part_numbers(&input_filepath, true));
+        }
+        ["day3", "gears", symbol, arity, aggregate] => {
+            let symbol = symbol.chars().next().expect("Missing symbol");
+            let arity = arity.parse::<usize>().expect("Cannot parse arity");
+            let aggregate = aoc3::parse_aggregate(aggregate).expect("Unknown aggregate");
+            aoc3::query_gears(&input_path("input_d03_t01"), symbol, arity, aggregate);
+        }
+        ["day3", "render", rest @ ..] => {
+            let colored = !rest.contains(&"--plain");
+            aoc3::render_schematic(&input_path("input_d03_t01"), colored);
+        }
+        ["day4"] => {
+            let input_filepath = input_path("input_d04_t01");
+            println!(
+                "Sum of winning powers: {}",
+                aoc4::solve_part_01(&input_filepath)
+            );
+            println!(
+                "Number of scratchcards: {}",
+                aoc4::solve_part_02(&input_filepath)
+            );
+        }
+        ["day4", "cascade", rest @ ..] => {
+            let as_dot = rest.contains(&"--dot");
+            aoc4::print_cascade(&input_path("input_d04_t01"), as_dot);
+        }
+        ["day5"] => {
+            let input_filepath = input_path("input_d05_t01");
+            println!("Minimal location: {}", aoc5::solve_part_01(&input_filepath));
+            println!(
+                "Minimal location for seed ranges: {}",
+                aoc5::solve_part_02(&input_filepath)
+            );
+        }
+        ["day5", "convert", from, to, value] => {
+            let value = value.parse::<u64>().expect("Cannot parse value");
+            aoc5::convert_value(&input_path("input_d05_t01"), from, to, value);
+        }
+        ["day5", "preimage", location, rest @ ..] => {
+            let location = location.parse::<u64>().expect("Cannot parse location");
+            let len = match rest.first() {
+                Some(len) => len.parse::<u64>().expect("Cannot parse range length"),
+                None => 1,
+            };
+            aoc5::print_preimage(&input_path("input_d05_t01"), location, location + len);
+        }
+        ["day5", "explain"] => {
+            aoc5::explain_lowest_location(&input_path("input_d05_t01"));
+        }
+        ["day5", "validate"] => {
+            aoc5::validate_almanac(&input_path("input_d05_t01"));
+        }
+        ["day5", "compose", rest @ ..] => {
+            let serialize = rest.contains(&"--serialize");
+            aoc5::print_composed(&input_path("input_d05_t01"), serialize);
+        }
+        ["day6"] => {
+            let input_filepath = input_path("input_d06_t01");
+            println!("Final result: {}", aoc6::solve_part_01(&input_filepath));
+            println!("Final result: {}", aoc6::solve_part_02(&input_filepath));
+        }
+        ["day7"] => {
+            let input_filepath = input_path("input_d07_t01");
+            println!("Total winnings {}", aoc7::solve_part_01(&input_filepath));
+            println!("Total winnings {}", aoc7::solve_part_02(&input_filepath));
+        }
+        ["day7", "report", rest @ ..] => {
+            let rules_name = rest.first().unwrap_or(&"standard");
+            aoc7::print_report(&input_path("input_d07_t01"), rules_name);
+        }
+        ["day7", "diff", first_rules_name, second_rules_name] => {
+            aoc7::print_diff(
+                &input_path("input_d07_t01"),
+                first_rules_name,
+                second_rules_name,
+            );
+        }
+        ["day8"] => {
+            let input_filepath = input_path("input_d08_t01");
+            println!(
+                "Find finish in {} steps",
+                aoc8::solve_part_01(&input_filepath)
+            );
+            println!(
+                "Ghosts arrive in {} steps",
+                aoc8::solve_part_02(&input_filepath)
+            );
+        }
+        ["day8", "network", rest @ ..] => {
+            let as_dot = rest.contains(&"--dot");
+            aoc8::print_network(&input_path("input_d08_t01"), as_dot);
+        }
+        ["day8", "walk", start, goal, rest @ ..] => {
+            let show_path = rest.contains(&"--path");
+            let instructions = rest.iter().find(|arg| !arg.starts_with("--")).copied();
+            aoc8::print_walks(
+                &input_path("input_d08_t01"),
+                start,
+                goal,
+                instructions,
+                show_path,
+            );
+        }
+        ["day9"] => {
+            let input_filepath = input_path("input_d09_t01");
+            println!(
+                "Sum of next values is: {}",
+                aoc9::solve_part_01(&input_filepath)
+            );
+            println!(
+                "Sum of prev values is: {}",
+                aoc9::solve_part_02(&input_filepath)
+            );
+        }
+        ["day9", "--method", method] => {
+            let input_filepath = input_path("input_d09_t01");
+            let method = aoc9::parse_method(method).expect("Unknown method");
+            println!(
+                "Sum of next values is: {}",
+                aoc9::sum_next_values(&input_filepath, method)
+            );
+            println!(
+                "Sum of prev values is: {}",
+                aoc9::sum_previous_values(&input_filepath, method)
+            );
+        }
+        ["day9", "check"] => {
+            aoc9::cross_check(&input_path("input_d09_t01"));
+        }
+        ["day9", "extrapolate", steps] => {
+            let steps = steps
+                .parse::<usize>()
+                .expect("Cannot parse number of steps");
+            aoc9::print_extrapolation(&input_path("input_d09_t01"), steps);
+        }
+        ["day9", "at", index] => {
+            let index = index.parse::<i64>().expect("Cannot parse index");
+            aoc9::print_values_at(&input_path("input_d09_t01"), index);
+        }
+        ["day10"] => {
+            let input_filepath = input_path("input_d10_t01");
+            println!(
+                "Farthest distance {}",
+                aoc10::solve_part_01(&input_filepath)
+            );
+            println!("Inside {}", aoc10::solve_part_02(&input_filepath));
+        }
+        [] => {
+            let input_filepath = input_path("input_d15_01");
+            let result = aoc15::solve_part_01(&input_filepath);
+            println!("And the result is {}", result);
 
-    let result = aoc15::solve_part_02(&input_filepath);
-    println!("And the result for part 2 is {}", result);
+            let result = aoc15::solve_part_02(&input_filepath);
+            println!("And the result for part 2 is {}", result);
+        }
+        _ => {
+            eprintln!("Unknown command '{}'", args.join(" "));
+            eprintln!("Usage: aoc-2023 [dayN [subcommand] [arguments]]");
+            std::process::exit(2);
+        }
+    }
 }
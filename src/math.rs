@@ -0,0 +1,193 @@
+use core::fmt;
+
+// Greatest common divisor, gcd(0, 0) is 0
+pub fn gcd(first: u128, second: u128) -> u128 {
+    let (mut max, mut min) = (first, second);
+    while min != 0 {
+        (max, min) = (min, max % min);
+    }
+    max
+}
+
+// Least common multiple, None when it does not fit into u128
+pub fn lcm(first: u128, second: u128) -> Option<u128> {
+    if first == 0 || second == 0 {
+        return Some(0);
+    }
+    (first / gcd(first, second)).checked_mul(second)
+}
+
+// Inverse of value modulo a positive modulus, in [0, modulus). Extended
+// Euclid keeps only the coefficient of value, reduced modulo the modulus, so
+// the whole u128 range works.
+pub fn mod_inverse(value: u128, modulus: u128) -> Option<u128> {
+    if modulus == 0 {
+        return None;
+    }
+    // old_x * value = old_r and x * value = r (mod modulus)
+    let (mut old_r, mut r) = (value % modulus, modulus);
+    let (mut old_x, mut x) = (1 % modulus, 0);
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_x, x) = (x, sub_mod(old_x, mul_mod(quotient, x, modulus), modulus));
+    }
+    match old_r {
+        1 => Some(old_x),
+        _ => None,
+    }
+}
+
+// (first * second) % modulus without overflowing in the product
+fn mul_mod(first: u128, second: u128, modulus: u128) -> u128 {
+    let (mut first, mut second) = (first % modulus, second % modulus);
+    if let Some(product) = first.checked_mul(second) {
+        return product % modulus;
+    }
+    let mut result = 0;
+    while second > 0 {
+        if second & 1 == 1 {
+            result = add_mod(result, first, modulus);
+        }
+        first = add_mod(first, first, modulus);
+        second >>= 1;
+    }
+    result
+}
+
+// Both values have to be already reduced
+fn sub_mod(first: u128, second: u128, modulus: u128) -> u128 {
+    match first >= second {
+        true => first - second,
+        false => modulus - (second - first),
+    }
+}
+
+// Both values have to be already reduced
+fn add_mod(first: u128, second: u128, modulus: u128) -> u128 {
+    match first >= modulus - second {
+        true => first - (modulus - second),
+        false => first + second,
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CrtError {
+    ZeroModulus,
+    NoSolution,
+    Overflow,
+}
+
+impl fmt::Display for CrtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CrtError::ZeroModulus => write!(f, "Modulus has to be positive"),
+            CrtError::NoSolution => write!(f, "Congruences have no common solution"),
+            CrtError::Overflow => write!(f, "Combined modulus does not fit into u128"),
+        }
+    }
+}
+
+// Combines x = first_residue (mod first_modulus) and x = second_residue
+// (mod second_modulus) into a single congruence, moduli do not have to be
+// coprime
+pub fn crt(
+    (first_residue, first_modulus): (u128, u128),
+    (second_residue, second_modulus): (u128, u128),
+) -> Result<(u128, u128), CrtError> {
+    if first_modulus == 0 || second_modulus == 0 {
+        return Err(CrtError::ZeroModulus);
+    }
+    let modulus = lcm(first_modulus, second_modulus).ok_or(CrtError::Overflow)?;
+    let gcd = gcd(first_modulus, second_modulus);
+
+    let (first_residue, second_residue) = (
+        first_residue % first_modulus,
+        second_residue % second_modulus,
+    );
+    let difference = match second_residue >= first_residue % second_modulus {
+        true => second_residue - first_residue % second_modulus,
+        false => second_modulus - (first_residue % second_modulus - second_residue),
+    };
+    if difference % gcd != 0 {
+        return Err(CrtError::NoSolution);
+    }
+
+    // first_residue + first_modulus * step solves both congruences, where
+    // step = (difference / gcd) * inverse(first_modulus / gcd) mod (second_modulus / gcd)
+    let reduced_modulus = second_modulus / gcd;
+    let inverse = mod_inverse(first_modulus / gcd, reduced_modulus).ok_or(CrtError::NoSolution)?;
+    let step = mul_mod(difference / gcd, inverse, reduced_modulus);
+
+    // first_modulus * step < first_modulus * reduced_modulus = modulus
+    let residue = (first_modulus * step)
+        .checked_add(first_residue)
+        .ok_or(CrtError::Overflow)?;
+    Ok((residue % modulus, modulus))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gcd_and_lcm() {
+        assert_eq!(gcd(0, 0), 0);
+        assert_eq!(gcd(0, 7), 7);
+        assert_eq!(gcd(12, 0), 12);
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(17, 5), 1);
+        assert_eq!(lcm(0, 5), Some(0));
+        assert_eq!(lcm(4, 6), Some(12));
+        assert_eq!(lcm(1 << 100, 1 << 120), Some(1 << 120));
+        assert_eq!(lcm(u128::MAX, u128::MAX - 1), None);
+    }
+
+    #[test]
+    fn test_mod_inverse() {
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(8, 11), Some(7));
+        assert_eq!(mod_inverse(25, 11), Some(4));
+        assert_eq!(mod_inverse(10, 1), Some(0));
+        assert_eq!(mod_inverse(6, 9), None);
+        assert_eq!(mod_inverse(3, 0), None);
+        assert_eq!(
+            mod_inverse(2, i128::MAX as u128),
+            Some(i128::MAX as u128 / 2 + 1)
+        );
+        assert_eq!(mod_inverse(2, u128::MAX), Some(1 << 127));
+        assert_eq!(mod_inverse(u128::MAX - 1, u128::MAX), Some(u128::MAX - 1));
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt((2, 3), (3, 5)), Ok((8, 15)));
+        assert_eq!(crt((1, 4), (3, 6)), Ok((9, 12)));
+        assert_eq!(crt((0, 4), (1, 6)), Err(CrtError::NoSolution));
+        assert_eq!(crt((0, 1), (5, 7)), Ok((5, 7)));
+        assert_eq!(crt((12, 5), (3, 7)), Ok((17, 35)));
+        assert_eq!(crt((1, 0), (1, 2)), Err(CrtError::ZeroModulus));
+        assert_eq!(crt((0, u128::MAX), (1, 2)), Err(CrtError::Overflow));
+        // Moduli above i128::MAX
+        let big = (1 << 127) + 1;
+        assert_eq!(crt((0, 1), (5, big)), Ok((5, big)));
+        assert_eq!(crt((5, big), (0, 1)), Ok((5, big)));
+        assert_eq!(crt((3, big), (3, big)), Ok((3, big)));
+        assert_eq!(crt((1, 2), (0, u128::MAX)), Err(CrtError::Overflow));
+
+        // Product of the moduli overflows u128, but their lcm does not
+        let residue = 5 + (1 << 90) + (1 << 110);
+        assert_eq!(
+            crt((5 + (1 << 90), 1 << 100), (residue, 1 << 120)),
+            Ok((residue, 1 << 120))
+        );
+        assert_eq!(
+            crt((5, 1 << 100), (residue, 1 << 120)),
+            Err(CrtError::NoSolution)
+        );
+        let (first, second) = ((1u128 << 64) - 59, (1u128 << 63) - 25);
+        let (residue, modulus) = crt((3, first), (7, second)).unwrap();
+        assert_eq!(modulus, first * second);
+        assert_eq!((residue % first, residue % second), (3, 7));
+    }
+}
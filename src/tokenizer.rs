@@ -0,0 +1,197 @@
+use std::{iter::Peekable, str::CharIndices};
+
+// Column counts characters, byte index points into the original line,
+// so both stay valid when the line contains non-ASCII characters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Position {
+    pub line_idx: usize,
+    pub col_idx: usize,
+    pub byte_idx: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    Number {
+        value: i64,
+        position: Position,
+        len: usize,
+    },
+    Symbol {
+        value: char,
+        position: Position,
+    },
+    // Digits which do not fit into i64
+    InvalidNumber {
+        position: Position,
+        len: usize,
+    },
+}
+
+// Splits a grid line into numbers and symbols, skipping '.' tiles
+pub struct TokenStream<'a> {
+    line: &'a str,
+    line_idx: usize,
+    col_idx: usize,
+    chars: Peekable<CharIndices<'a>>,
+    signed_numbers: bool,
+}
+
+impl<'a> TokenStream<'a> {
+    pub fn new(line: &'a str, line_idx: usize) -> TokenStream<'a> {
+        TokenStream {
+            line,
+            line_idx,
+            col_idx: 0,
+            chars: line.char_indices().peekable(),
+            signed_numbers: false,
+        }
+    }
+
+    // When enabled, '-' directly followed by a digit starts a negative number
+    // instead of being a symbol
+    pub fn signed_numbers(mut self, signed_numbers: bool) -> TokenStream<'a> {
+        self.signed_numbers = signed_numbers;
+        self
+    }
+
+    fn next_char(&mut self) -> Option<(Position, char)> {
+        let (byte_idx, char) = self.chars.next()?;
+        let position = Position {
+            line_idx: self.line_idx,
+            col_idx: self.col_idx,
+            byte_idx,
+        };
+        self.col_idx += 1;
+        Some((position, char))
+    }
+
+    fn next_is_digit(&mut self) -> bool {
+        matches!(self.chars.peek(), Some((_, char)) if char.is_ascii_digit())
+    }
+}
+
+impl Iterator for TokenStream<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        loop {
+            let (position, char) = self.next_char()?;
+            if char == '.' {
+                continue;
+            }
+
+            let is_sign = self.signed_numbers && char == '-' && self.next_is_digit();
+            if !char.is_ascii_digit() && !is_sign {
+                return Some(Token::Symbol {
+                    value: char,
+                    position,
+                });
+            }
+
+            let mut len = 1;
+            while self.next_is_digit() {
+                self.next_char();
+                len += 1;
+            }
+            // Number consists only of ASCII characters, so its length in bytes
+            // equals its length in characters
+            let number = &self.line[position.byte_idx..position.byte_idx + len];
+            return match number.parse::<i64>() {
+                Ok(value) => Some(Token::Number {
+                    value,
+                    position,
+                    len,
+                }),
+                Err(_) => Some(Token::InvalidNumber { position, len }),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positions_with_non_ascii_symbols() {
+        let tokens: Vec<_> = TokenStream::new("§12.é..345", 3).collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Symbol {
+                    value: '§',
+                    position: Position {
+                        line_idx: 3,
+                        col_idx: 0,
+                        byte_idx: 0
+                    }
+                },
+                Token::Number {
+                    value: 12,
+                    position: Position {
+                        line_idx: 3,
+                        col_idx: 1,
+                        byte_idx: 2
+                    },
+                    len: 2
+                },
+                Token::Symbol {
+                    value: 'é',
+                    position: Position {
+                        line_idx: 3,
+                        col_idx: 4,
+                        byte_idx: 5
+                    }
+                },
+                Token::Number {
+                    value: 345,
+                    position: Position {
+                        line_idx: 3,
+                        col_idx: 7,
+                        byte_idx: 9
+                    },
+                    len: 3
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_signed_numbers() {
+        let values = |tokens: Vec<Token>| -> Vec<String> {
+            tokens
+                .iter()
+                .map(|token| match token {
+                    Token::Number { value, .. } => value.to_string(),
+                    Token::Symbol { value, .. } => value.to_string(),
+                    Token::InvalidNumber { .. } => "invalid".to_string(),
+                })
+                .collect()
+        };
+
+        let unsigned: Vec<_> = TokenStream::new("-12.3-4-", 0).collect();
+        assert_eq!(values(unsigned), vec!["-", "12", "3", "-", "4", "-"]);
+
+        let signed: Vec<_> = TokenStream::new("-12.3-4-", 0)
+            .signed_numbers(true)
+            .collect();
+        assert_eq!(values(signed), vec!["-12", "3", "-4", "-"]);
+    }
+
+    #[test]
+    fn test_numbers_out_of_range() {
+        let tokens: Vec<_> = TokenStream::new("*99999999999999999999.1", 0).collect();
+        assert_eq!(
+            tokens[1],
+            Token::InvalidNumber {
+                position: Position {
+                    line_idx: 0,
+                    col_idx: 1,
+                    byte_idx: 1
+                },
+                len: 20
+            }
+        );
+        assert_eq!(tokens.len(), 3);
+    }
+}